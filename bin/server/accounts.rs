@@ -0,0 +1,185 @@
+// bin/server/accounts.rs
+// 玩家账号：用户名 + Argon2 哈希密码，登录后签发会话令牌，数据保存在 SQLite
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::db;
+use crate::lobby::now_ms;
+
+/// 用户名长度范围（字符）
+const USERNAME_LEN: std::ops::RangeInclusive<usize> = 3..=16;
+/// 密码最短长度
+const MIN_PASSWORD_LEN: usize = 6;
+/// 登录令牌有效期，期间客户端可凭令牌自动登录
+const SESSION_TTL: Duration = Duration::from_secs(30 * 24 * 3600);
+
+pub type SharedAccounts = Arc<Mutex<AccountStore>>;
+
+#[derive(Debug, Clone)]
+pub struct Account {
+    pub id: i64,
+    pub username: String,
+}
+
+/// 账号玩家标识的前缀；匿名玩家上报的本地标识不能以此开头
+pub const ACCOUNT_IDENTITY_PREFIX: &str = "account:";
+
+impl Account {
+    /// 积分等跨会话数据使用的玩家标识（与匿名玩家的本地标识区分）
+    pub fn identity(&self) -> String {
+        format!("{}{}", ACCOUNT_IDENTITY_PREFIX, self.id)
+    }
+}
+
+pub struct AccountStore {
+    conn: Connection,
+}
+
+impl AccountStore {
+    /// 打开数据库并建表
+    pub fn open() -> rusqlite::Result<Self> {
+        let conn = db::open()?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS accounts (
+                id INTEGER PRIMARY KEY,
+                username TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                password_hash TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS sessions (
+                token TEXT PRIMARY KEY,
+                account_id INTEGER NOT NULL REFERENCES accounts(id),
+                expires_at INTEGER NOT NULL
+            );",
+        )?;
+        Ok(Self { conn })
+    }
+
+    pub fn shared() -> rusqlite::Result<SharedAccounts> {
+        Ok(Arc::new(Mutex::new(Self::open()?)))
+    }
+
+    /// 注册新账号（用户名不区分大小写），成功后直接签发登录令牌
+    pub fn register(&mut self, username: &str, password: &str) -> Result<(Account, String), String> {
+        let username = username.trim();
+        validate_username(username)?;
+        if password.chars().count() < MIN_PASSWORD_LEN {
+            return Err(format!("密码至少 {} 个字符", MIN_PASSWORD_LEN));
+        }
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(internal)?
+            .to_string();
+        let inserted = self.conn.execute(
+            "INSERT INTO accounts (username, password_hash, created_at) VALUES (?1, ?2, ?3)",
+            params![username, hash, now_ms() as i64],
+        );
+        match inserted {
+            Ok(_) => {}
+            Err(rusqlite::Error::SqliteFailure(e, _))
+                if e.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                return Err("用户名已被注册".to_string());
+            }
+            Err(e) => return Err(internal(e)),
+        }
+        let account = Account { id: self.conn.last_insert_rowid(), username: username.to_string() };
+        let token = self.create_session(&account)?;
+        Ok((account, token))
+    }
+
+    /// 用户名密码登录，成功后签发登录令牌
+    pub fn login(&mut self, username: &str, password: &str) -> Result<(Account, String), String> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT id, username, password_hash FROM accounts WHERE username = ?1",
+                params![username.trim()],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)),
+            )
+            .optional()
+            .map_err(internal)?;
+        // 用户名不存在与密码错误使用同一提示，避免被用来探测已注册的用户名
+        let Some((id, username, _)) = row.filter(|(_, _, hash)| verify(password, hash)) else {
+            return Err("用户名或密码错误".to_string());
+        };
+        let account = Account { id, username };
+        let token = self.create_session(&account)?;
+        Ok((account, token))
+    }
+
+    /// 凭登录令牌找回账号，令牌不存在或已过期时返回 None
+    pub fn authenticate(&mut self, token: &str) -> Result<Option<Account>, String> {
+        self.conn
+            .query_row(
+                "SELECT accounts.id, accounts.username FROM sessions
+                 JOIN accounts ON accounts.id = sessions.account_id
+                 WHERE sessions.token = ?1 AND sessions.expires_at > ?2",
+                params![token, now_ms() as i64],
+                |row| Ok(Account { id: row.get(0)?, username: row.get(1)? }),
+            )
+            .optional()
+            .map_err(internal)
+    }
+
+    /// 注销登录令牌
+    pub fn logout(&mut self, token: &str) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM sessions WHERE token = ?1", params![token])
+            .map(|_| ())
+            .map_err(internal)
+    }
+
+    fn create_session(&mut self, account: &Account) -> Result<String, String> {
+        let token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(40)
+            .map(char::from)
+            .collect();
+        let now = now_ms() as i64;
+        // 顺便清理过期令牌
+        self.conn
+            .execute("DELETE FROM sessions WHERE expires_at <= ?1", params![now])
+            .map_err(internal)?;
+        self.conn
+            .execute(
+                "INSERT INTO sessions (token, account_id, expires_at) VALUES (?1, ?2, ?3)",
+                params![token, account.id, now + SESSION_TTL.as_millis() as i64],
+            )
+            .map_err(internal)?;
+        Ok(token)
+    }
+}
+
+/// 用户名只允许字母、数字（含中文）和下划线
+fn validate_username(username: &str) -> Result<(), String> {
+    let len = username.chars().count();
+    if !USERNAME_LEN.contains(&len) {
+        return Err(format!(
+            "用户名长度需为 {}~{} 个字符",
+            USERNAME_LEN.start(),
+            USERNAME_LEN.end()
+        ));
+    }
+    if !username.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err("用户名只能包含字母、数字和下划线".to_string());
+    }
+    Ok(())
+}
+
+fn verify(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .is_ok_and(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+}
+
+/// 数据库错误只记录日志，返回给客户端通用提示
+fn internal(e: impl std::fmt::Display) -> String {
+    eprintln!("Account store error: {}", e);
+    "服务器内部错误，请稍后再试".to_string()
+}
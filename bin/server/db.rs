@@ -0,0 +1,29 @@
+// bin/server/db.rs
+// 服务端 SQLite 数据库：账号、对局记录与积分共用一个文件，各模块各自打开连接并建表
+use std::time::Duration;
+use rusqlite::Connection;
+
+/// 数据库默认路径，可通过环境变量 `SNAKE_DB_FILE` 覆盖
+const DEFAULT_DB_FILE: &str = "snake.db";
+/// 其他连接正在写入时的等待时长
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 打开数据库；使用 WAL 模式，多个连接可以同时读写
+pub fn open() -> rusqlite::Result<Connection> {
+    let path = std::env::var("SNAKE_DB_FILE").unwrap_or_else(|_| DEFAULT_DB_FILE.to_string());
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    Ok(conn)
+}
+
+/// 为旧版本创建的表补上新增的列（新建的表已包含该列时不做处理）
+pub fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    let exists = conn
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))?
+        .exists([column])?;
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
+    Ok(())
+}
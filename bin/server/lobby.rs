@@ -0,0 +1,945 @@
+// bin/server/lobby.rs
+// 匹配队列与房间管理：所有状态放在一把锁里，每个进行中的对局一个 tick 任务，另有一个定时撮合任务
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use tokio::sync::mpsc::UnboundedSender;
+use snake_game::delta;
+use snake_game::engine::{self, Engine, EngineConfig, InputQueue};
+use snake_game::profile;
+use snake_game::settings;
+use snake_game::types::{
+    DeathCause, Direction, GameEvent, GameMessage, GameState, PlayerProfile, RoomDetails, RoomInfo, RoomMember,
+    RoomSettings,
+};
+use crate::accounts::{Account, ACCOUNT_IDENTITY_PREFIX};
+use crate::ratings::RatingStore;
+use crate::results::{MatchPlayer, MatchRecord};
+
+/// 快速匹配每局所需玩家数（与前端初始的 `(0, 2)` 一致）
+pub const REQUIRED_PLAYERS: usize = 2;
+/// 对局中断线后保留蛇的时长，期间可凭令牌恢复会话
+pub const RESUME_GRACE: Duration = Duration::from_secs(15);
+/// 房间码长度
+const ROOM_CODE_LEN: usize = 6;
+// 房间码字符集：去掉容易混淆的 0/O、1/I
+const ROOM_CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+/// 玩家标识的最大长度，超出的标识视为无效
+const MAX_PLAYER_KEY_LEN: usize = 64;
+/// 快速匹配的初始积分窗口：同一局内最高与最低积分之差不超过该值
+const RATING_WINDOW: f64 = 100.0;
+/// 每等待 `RATING_WINDOW_STEP` 窗口放宽 `RATING_WINDOW_GROWTH`，最多放宽到 `MAX_RATING_WINDOW`
+const RATING_WINDOW_STEP: Duration = Duration::from_secs(5);
+const RATING_WINDOW_GROWTH: f64 = 50.0;
+const MAX_RATING_WINDOW: f64 = 1000.0;
+/// 定时撮合的间隔：等待中的玩家窗口放宽后无需新玩家加入也能配对
+pub const MATCHMAKING_INTERVAL: Duration = Duration::from_secs(1);
+
+pub type SharedLobby = Arc<Mutex<Lobby>>;
+
+// ---------------- 玩家与房间 ----------------
+pub struct Player {
+    // 断线期间为 None
+    tx: Option<UnboundedSender<GameMessage>>,
+    token: String,
+    // 所在房间的房间码
+    room: Option<String>,
+    disconnected_at: Option<Instant>,
+    // 客户端发来的持久化玩家标识，未标识的玩家不计积分
+    key: Option<String>,
+    // 已通过校验的玩家资料，开局时写入蛇
+    profile: PlayerProfile,
+    // 已登录的账号及其登录令牌
+    account: Option<(Account, String)>,
+}
+
+impl Player {
+    /// 积分使用的玩家标识：已登录时为账号，否则为客户端的本地标识
+    fn identity(&self) -> Option<String> {
+        match &self.account {
+            Some((account, _)) => Some(account.identity()),
+            None => self.key.clone(),
+        }
+    }
+
+    /// 排行榜等处显示的名字：昵称优先，其次账号用户名
+    fn display_name(&self, id: usize) -> String {
+        match &self.account {
+            Some((account, _)) if self.profile.name.is_empty() => account.username.clone(),
+            _ => self.profile.display_name(id),
+        }
+    }
+}
+
+/// 快速匹配队列中的玩家
+struct Queued {
+    id: usize,
+    // 入队时的积分，用于分组
+    rating: f64,
+    since: Instant,
+}
+
+impl Queued {
+    /// 当前可接受的积分差：等待越久越宽
+    fn window(&self) -> f64 {
+        let steps = (self.since.elapsed().as_secs_f64() / RATING_WINDOW_STEP.as_secs_f64()).floor();
+        (RATING_WINDOW + steps * RATING_WINDOW_GROWTH).min(MAX_RATING_WINDOW)
+    }
+}
+
+/// 进行中的一局
+struct Game {
+    // 即 GameState.room_id，每局唯一
+    id: String,
+    players: Vec<usize>,
+    engine: Engine,
+    state: GameState,
+    // 最近一次广播的状态，作为下一次增量的基准：两个 tick 之间的判负只改 `state`，
+    // 从这里算起的增量才包含这些死亡
+    sent: GameState,
+    // 各玩家排队的转向，每个 tick 取出一个
+    input_queues: HashMap<usize, InputQueue>,
+    // 开局时有玩家标识的参与者
+    participants: HashMap<usize, Participant>,
+    // 各蛇在本局中达到的最大长度
+    max_lengths: HashMap<usize, usize>,
+    // 两个 tick 之间产生的事件（离开、掉线判负），随下一 tick 的状态下发
+    pending_events: Vec<GameEvent>,
+    // 快速匹配的对局计积分
+    rated: bool,
+    started_at: u64,
+}
+
+impl Game {
+    /// 判负（离开或掉线）：蛇立即死亡，事件在下一 tick 下发
+    fn eliminate(&mut self, id: usize, cause: DeathCause) {
+        if let Some(snake) = self.state.snakes.iter_mut().find(|s| s.id == id && s.alive) {
+            snake.alive = false;
+            snake.death = Some(cause);
+            self.pending_events.push(GameEvent::Died { id, cause, killer: None });
+        }
+    }
+}
+
+/// 参与者开局时的标识与显示名（中途离开后仍用于结算）
+struct Participant {
+    identity: String,
+    name: String,
+}
+
+pub struct Room {
+    code: String,
+    settings: RoomSettings,
+    // 玩家创建的房间可被列出、凭房间码加入，对局结束后保留；
+    // 快速匹配的房间只用于一局，结束后解散
+    custom: bool,
+    members: Vec<usize>,
+    ready: HashSet<usize>,
+    game: Option<Game>,
+}
+
+impl Room {
+    fn info(&self) -> RoomInfo {
+        RoomInfo {
+            code: self.code.clone(),
+            name: self.settings.name.clone(),
+            players: self.members.len(),
+            max_players: self.settings.max_players,
+            in_game: self.game.is_some(),
+        }
+    }
+
+    fn details(&self, players: &HashMap<usize, Player>) -> RoomDetails {
+        RoomDetails {
+            info: self.info(),
+            settings: self.settings.clone(),
+            members: self
+                .members
+                .iter()
+                .map(|&id| RoomMember {
+                    id,
+                    ready: self.ready.contains(&id),
+                    profile: players.get(&id).map(|p| p.profile.clone()).unwrap_or_default(),
+                })
+                .collect(),
+        }
+    }
+}
+
+// ---------------- 大厅 ----------------
+#[derive(Default)]
+pub struct Lobby {
+    next_player_id: usize,
+    next_game_id: usize,
+    players: HashMap<usize, Player>,
+    queue: Vec<Queued>,
+    rooms: HashMap<String, Room>,
+    ratings: RatingStore,
+    // 对局记录的写入通道
+    results: Option<UnboundedSender<MatchRecord>>,
+}
+
+impl Lobby {
+    pub fn shared(ratings: RatingStore, results: UnboundedSender<MatchRecord>) -> SharedLobby {
+        Arc::new(Mutex::new(Lobby {
+            ratings,
+            results: Some(results),
+            ..Lobby::default()
+        }))
+    }
+
+    /// 新连接：分配玩家ID（同时作为蛇ID）和会话令牌，并推送等待中的空状态，让前端显示准备按钮
+    pub fn connect(&mut self, tx: UnboundedSender<GameMessage>) -> usize {
+        self.next_player_id += 1;
+        let id = self.next_player_id;
+        let token = session_token();
+        self.players.insert(id, Player {
+            tx: Some(tx),
+            token: token.clone(),
+            room: None,
+            disconnected_at: None,
+            key: None,
+            profile: PlayerProfile::default(),
+            account: None,
+        });
+        self.send(id, GameMessage::Session { token });
+        self.send(id, GameMessage::Welcome { player_id: id, room_id: String::new() });
+        self.send(id, GameMessage::GameState(waiting_state()));
+        self.send(id, self.matching_status());
+        id
+    }
+
+    /// 连接断开：移出匹配队列；若在对局中则保留蛇 `RESUME_GRACE` 时长等待恢复，
+    /// 否则离开所在房间
+    ///
+    /// `tx` 用于识别连接：会话已被新连接接管时，旧连接的断开不做处理。
+    pub fn disconnect(&mut self, id: usize, tx: &UnboundedSender<GameMessage>) {
+        let current = self
+            .players
+            .get(&id)
+            .and_then(|p| p.tx.as_ref())
+            .is_some_and(|p| p.same_channel(tx));
+        if !current {
+            return;
+        }
+        self.leave_queue(id);
+        if !self.in_game(id) {
+            self.leave_room(id);
+            self.players.remove(&id);
+            return;
+        }
+        if let Some(player) = self.players.get_mut(&id) {
+            player.tx = None;
+            player.disconnected_at = Some(Instant::now());
+        }
+    }
+
+    /// 凭令牌恢复会话：把当前连接（临时玩家 `conn_id`）重新绑定到原玩家，返回原玩家ID
+    pub fn resume(&mut self, conn_id: usize, token: &str) -> Option<usize> {
+        let id = self
+            .players
+            .iter()
+            .find(|(&id, p)| id != conn_id && p.token == token)
+            .map(|(&id, _)| id)?;
+
+        // 当前连接通常是刚建立的，但也可能已经排队或进了房间：先退出，避免留下失效的成员
+        self.players.get(&conn_id)?;
+        self.leave_queue(conn_id);
+        self.leave_room(conn_id);
+        let temp = self.players.remove(&conn_id)?;
+        let player = self.players.get_mut(&id)?;
+        player.tx = temp.tx;
+        player.disconnected_at = None;
+
+        self.send(id, GameMessage::Session { token: token.to_string() });
+        let room = self.player_room(id);
+        let (room_id, state) = match room.and_then(|room| room.game.as_ref()) {
+            Some(game) => (game.id.clone(), game.state.clone()),
+            None => (String::new(), waiting_state()),
+        };
+        let details = room.filter(|room| room.custom).map(|room| room.details(&self.players));
+        self.send(id, GameMessage::Welcome { player_id: id, room_id });
+        self.send(id, GameMessage::GameState(state));
+        if let Some(details) = details {
+            self.send(id, GameMessage::RoomUpdate(details));
+        }
+        Some(id)
+    }
+
+    /// 客户端发来持久化的玩家标识：记录下来并推送该标识的积分（冒用账号标识的请求直接忽略）
+    pub fn identify(&mut self, id: usize, key: &str) {
+        let key = key.trim();
+        // 账号标识的命名空间只属于已登录的玩家，否则匿名客户端可以冒用账号的积分与战绩
+        if key.is_empty() || key.len() > MAX_PLAYER_KEY_LEN || key.starts_with(ACCOUNT_IDENTITY_PREFIX) {
+            return;
+        }
+        let Some(player) = self.players.get_mut(&id) else {
+            return;
+        };
+        player.key = Some(key.to_string());
+        self.send_rating(id);
+    }
+
+    /// 登录成功：绑定账号并回传登录令牌，之后积分记在账号上
+    pub fn login(&mut self, id: usize, account: Account, token: String) {
+        let Some(player) = self.players.get_mut(&id) else {
+            return;
+        };
+        let username = account.username.clone();
+        player.account = Some((account, token.clone()));
+        self.send(id, GameMessage::LoggedIn { username, token });
+        self.send_rating(id);
+    }
+
+    /// 退出登录，返回需要注销的登录令牌
+    pub fn logout(&mut self, id: usize) -> Option<String> {
+        let (_, token) = self.players.get_mut(&id)?.account.take()?;
+        self.send(id, GameMessage::LoggedOut);
+        self.send_rating(id);
+        Some(token)
+    }
+
+    /// 设置玩家资料：校验通过后保存并回传规范化的结果，下一局开始生效
+    pub fn set_profile(&mut self, id: usize, profile: PlayerProfile) {
+        let profile = profile::normalize(profile);
+        if let Err(message) = profile::validate(&profile) {
+            self.send(id, GameMessage::ProfileError { message });
+            return;
+        }
+        let Some(player) = self.players.get_mut(&id) else {
+            return;
+        };
+        player.profile = profile.clone();
+        let room = player.room.clone();
+        self.send(id, GameMessage::Profile { profile });
+        if let Some(code) = room {
+            self.broadcast_room(&code);
+        }
+    }
+
+    /// 玩家准备：在房间中时标记为已准备，全员准备且达到房间最少人数时开局；
+    /// 不在房间中时加入快速匹配队列并尝试撮合。返回开局的房间码
+    pub fn ready(&mut self, id: usize) -> Vec<String> {
+        if let Some(code) = self.players.get(&id).and_then(|p| p.room.clone()) {
+            return self.ready_in_room(id, &code).into_iter().collect();
+        }
+        if !self.queue.iter().any(|q| q.id == id) {
+            let rating = self.rating_of(id);
+            self.queue.push(Queued { id, rating, since: Instant::now() });
+        }
+        // 重新开始时前端还停留在上一局的结束画面，先推送等待状态
+        self.send(id, GameMessage::GameState(waiting_state()));
+        self.broadcast_matching_status();
+        self.match_queue()
+    }
+
+    /// 按积分撮合队列中的玩家：按积分排序后取相邻的 `REQUIRED_PLAYERS` 人，
+    /// 最高与最低积分之差不超过其中每个人当前的窗口时开局。返回开局的房间码
+    pub fn match_queue(&mut self) -> Vec<String> {
+        let mut started = vec![];
+        loop {
+            self.queue.sort_by(|a, b| a.rating.total_cmp(&b.rating));
+            let group = self.queue.windows(REQUIRED_PLAYERS).position(|group| {
+                let spread = group[group.len() - 1].rating - group[0].rating;
+                group.iter().all(|q| spread <= q.window())
+            });
+            let Some(start) = group else {
+                break;
+            };
+            let players: Vec<usize> = self
+                .queue
+                .drain(start..start + REQUIRED_PLAYERS)
+                .map(|q| q.id)
+                .collect();
+            let settings = RoomSettings { min_players: REQUIRED_PLAYERS, ..RoomSettings::default() };
+            let code = self.insert_room(settings, false, players);
+            self.start_game(&code);
+            started.push(code);
+        }
+        if !started.is_empty() {
+            self.broadcast_matching_status();
+        }
+        started
+    }
+
+    fn ready_in_room(&mut self, id: usize, code: &str) -> Option<String> {
+        let room = self.rooms.get_mut(code)?;
+        if room.game.is_some() {
+            return None;
+        }
+        room.ready.insert(id);
+        let start = room.members.len() >= room.settings.min_players
+            && room.members.iter().all(|m| room.ready.contains(m));
+        self.send(id, GameMessage::GameState(waiting_state()));
+        self.broadcast_room(code);
+        if !start {
+            return None;
+        }
+        self.start_game(code);
+        Some(code.to_string())
+    }
+
+    /// 校验设置后创建房间并加入；名称为空时使用房间码
+    pub fn create_room(&mut self, id: usize, mut settings: RoomSettings) {
+        if self.in_game(id) {
+            self.send(id, room_error("对局进行中，无法创建房间"));
+            return;
+        }
+        if let Err(message) = settings::validate(&settings) {
+            self.send(id, GameMessage::RoomError { message });
+            return;
+        }
+        self.leave_queue(id);
+        self.leave_room(id);
+        settings.name = settings.name.trim().chars().take(settings::MAX_NAME_LEN).collect();
+        let code = self.insert_room(settings, true, vec![id]);
+        if let Some(room) = self.rooms.get_mut(&code) {
+            if room.settings.name.is_empty() {
+                room.settings.name = format!("房间 {}", code);
+            }
+        }
+        self.broadcast_room(&code);
+    }
+
+    /// 凭房间码加入房间（不区分大小写）
+    pub fn join_room(&mut self, id: usize, code: &str) {
+        let code = code.trim().to_uppercase();
+        // 已在该房间中（例如重连后重新加入）：只重发房间信息
+        if let Some(room) = self.player_room(id).filter(|room| room.code == code && room.custom) {
+            self.send(id, GameMessage::RoomUpdate(room.details(&self.players)));
+            return;
+        }
+        let error = match self.rooms.get(&code) {
+            None => Some("房间不存在"),
+            Some(room) if !room.custom => Some("房间不存在"),
+            Some(room) if room.game.is_some() => Some("房间正在对局中"),
+            Some(room) if room.members.len() >= room.settings.max_players => Some("房间已满"),
+            Some(_) if self.in_game(id) => Some("对局进行中，无法加入其他房间"),
+            Some(_) => None,
+        };
+        if let Some(message) = error {
+            self.send(id, room_error(message));
+            return;
+        }
+        self.leave_queue(id);
+        self.leave_room(id);
+        if let Some(room) = self.rooms.get_mut(&code) {
+            room.members.push(id);
+        }
+        if let Some(player) = self.players.get_mut(&id) {
+            player.room = Some(code.clone());
+        }
+        self.broadcast_room(&code);
+    }
+
+    /// 离开所在房间：对局中离开视为认输（蛇立即死亡）；房间空了则解散
+    pub fn leave_room(&mut self, id: usize) {
+        let Some(code) = self.players.get_mut(&id).and_then(|p| p.room.take()) else {
+            return;
+        };
+        let Some(room) = self.rooms.get_mut(&code) else {
+            return;
+        };
+        room.members.retain(|&m| m != id);
+        room.ready.remove(&id);
+        if let Some(game) = room.game.as_mut() {
+            game.players.retain(|&p| p != id);
+            game.eliminate(id, DeathCause::Left);
+        }
+        let empty = room.members.is_empty();
+        self.send(id, GameMessage::RoomLeft);
+        self.send(id, GameMessage::GameState(waiting_state()));
+        if empty {
+            self.rooms.remove(&code);
+        } else {
+            self.broadcast_room(&code);
+        }
+    }
+
+    /// 发送可加入的房间列表（只包含玩家创建的房间）
+    pub fn list_rooms(&self, id: usize) {
+        let mut rooms: Vec<RoomInfo> = self
+            .rooms
+            .values()
+            .filter(|room| room.custom)
+            .map(Room::info)
+            .collect();
+        rooms.sort_by(|a, b| a.name.cmp(&b.name).then(a.code.cmp(&b.code)));
+        self.send(id, GameMessage::RoomList { rooms });
+    }
+
+    /// 玩家输入进入该玩家的输入队列，之后每个 tick 生效一个；
+    /// 队列已满或与有效方向相同、相反的输入被丢弃（见 `engine::accepts_turn`）。
+    /// `seq` 为客户端输入序号（旧协议的输入没有序号）
+    pub fn input(&mut self, id: usize, direction: Direction, seq: Option<u32>) {
+        let Some(game) = self.player_game_mut(id) else {
+            return;
+        };
+        let Some(snake) = game.state.snakes.iter().find(|s| s.id == id && s.alive) else {
+            return;
+        };
+        let depth = game.engine.config().input_queue_depth;
+        game.input_queues.entry(id).or_default().push(snake, direction, seq, depth);
+    }
+
+    /// 客户端请求完整快照（增量缺失或乱序时）
+    pub fn snapshot(&mut self, id: usize) {
+        let state = self
+            .player_room(id)
+            .and_then(|room| room.game.as_ref())
+            .map(|game| game.state.clone());
+        if let Some(state) = state {
+            self.send(id, GameMessage::GameState(state));
+        }
+    }
+
+    /// 推进房间内对局 `game_id` 一个 tick 并广播状态；
+    /// 对局结束（或房间、对局已不存在）时返回 false
+    pub fn tick_room(&mut self, code: &str, game_id: &str) -> bool {
+        let Some(game) = self
+            .rooms
+            .get_mut(code)
+            .and_then(|room| room.game.as_mut())
+            .filter(|game| game.id == game_id)
+        else {
+            return false;
+        };
+        // 超过宽限期仍未恢复的玩家判负
+        let expired: Vec<usize> = game
+            .players
+            .iter()
+            .copied()
+            .filter(|id| {
+                self.players
+                    .get(id)
+                    .and_then(|p| p.disconnected_at)
+                    .is_some_and(|at| at.elapsed() >= RESUME_GRACE)
+            })
+            .collect();
+        for id in expired {
+            game.eliminate(id, DeathCause::Disconnected);
+        }
+
+        let inputs: Vec<(usize, Direction)> = game
+            .input_queues
+            .iter_mut()
+            .filter_map(|(&id, queue)| Some((id, queue.pop()?)))
+            .collect();
+        game.state = game.engine.step(&game.state, &inputs);
+        game.state.server_time_ms = now_ms();
+        game.state.events.splice(0..0, game.pending_events.drain(..));
+        // 回传已处理（生效或被丢弃）的输入序号
+        for (id, queue) in &game.input_queues {
+            if let Some(snake) = game.state.snakes.iter_mut().find(|s| s.id == *id) {
+                snake.last_input_seq = queue.processed();
+            }
+        }
+        for snake in &game.state.snakes {
+            let longest = game.max_lengths.entry(snake.id).or_default();
+            *longest = (*longest).max(snake.body.len());
+        }
+
+        // 优先发送增量，无法用增量表示时发送完整状态
+        let state = game.state.clone();
+        let players = game.players.clone();
+        let msg = match delta::diff(&game.sent, &state) {
+            Some(delta) => GameMessage::StateDelta(delta),
+            None => GameMessage::GameState(state.clone()),
+        };
+        game.sent = state.clone();
+        for &id in &players {
+            self.send(id, msg.clone());
+        }
+        if !state.game_over {
+            return true;
+        }
+
+        let rankings = engine::rankings(&state);
+        for &id in &players {
+            self.send(id, GameMessage::GameOver { rankings: rankings.clone() });
+        }
+        self.record_results(code, &state, &rankings);
+        self.finish_game(code);
+        false
+    }
+
+    /// 对局结算：提交对局记录，计分对局还要更新积分并推送给仍在线的玩家。
+    /// 只统计开局时有玩家标识的参与者（中途离开的同样计入）；
+    /// 名次与排名一致（分数高者在前，同分时存活者在前），分数与存活状态都相同的并列
+    fn record_results(&mut self, code: &str, state: &GameState, rankings: &[(usize, u32)]) {
+        let Some(game) = self.rooms.get(code).and_then(|room| room.game.as_ref()) else {
+            return;
+        };
+        let outcome = |id: usize, score: u32| {
+            let alive = state.snakes.iter().any(|s| s.id == id && s.alive);
+            (score, alive)
+        };
+        let players: Vec<(usize, MatchPlayer)> = rankings
+            .iter()
+            .filter_map(|&(id, score)| {
+                let participant = game.participants.get(&id)?;
+                let snake = state.snakes.iter().find(|s| s.id == id);
+                let place = rankings
+                    .iter()
+                    .position(|&(other, other_score)| outcome(other, other_score) == outcome(id, score))
+                    .unwrap_or_default();
+                Some((id, MatchPlayer {
+                    identity: participant.identity.clone(),
+                    name: participant.name.clone(),
+                    score,
+                    place: place as u32,
+                    max_length: game.max_lengths.get(&id).copied().unwrap_or_default() as u32,
+                    death: snake.and_then(|s| s.death),
+                }))
+            })
+            .collect();
+        if players.is_empty() {
+            return;
+        }
+        if let Some(results) = &self.results {
+            let _ = results.send(MatchRecord {
+                mode: game.engine.config().win_condition.mode(),
+                rated: game.rated,
+                started_at_ms: game.started_at,
+                ended_at_ms: now_ms(),
+                total_players: state.snakes.len() as u32,
+                players: players.iter().map(|(_, player)| player.clone()).collect(),
+            });
+        }
+        if !game.rated || players.len() < 2 {
+            return;
+        }
+        let results: Vec<(String, u32)> = players
+            .iter()
+            .map(|(_, player)| (player.identity.clone(), player.place))
+            .collect();
+        self.ratings.record(&results);
+        for (id, _) in players {
+            self.send_rating(id);
+        }
+    }
+
+    /// 对局结束：快速匹配的房间解散，玩家创建的房间回到准备阶段；
+    /// 对局结束时仍处于断线状态的玩家不再保留
+    fn finish_game(&mut self, code: &str) {
+        let Some(room) = self.rooms.get_mut(code) else {
+            return;
+        };
+        room.game = None;
+        room.ready.clear();
+        let members = room.members.clone();
+        if !room.custom {
+            self.rooms.remove(code);
+            for &id in &members {
+                if let Some(player) = self.players.get_mut(&id) {
+                    player.room = None;
+                }
+            }
+        }
+        for id in members {
+            if self.players.get(&id).is_some_and(|p| p.tx.is_none()) {
+                self.leave_room(id);
+                self.players.remove(&id);
+            }
+        }
+        self.broadcast_room(code);
+    }
+
+    fn insert_room(&mut self, settings: RoomSettings, custom: bool, members: Vec<usize>) -> String {
+        let code = loop {
+            let code = room_code();
+            if !self.rooms.contains_key(&code) {
+                break code;
+            }
+        };
+        for &id in &members {
+            if let Some(player) = self.players.get_mut(&id) {
+                player.room = Some(code.clone());
+            }
+        }
+        self.rooms.insert(code.clone(), Room {
+            code: code.clone(),
+            settings,
+            custom,
+            members,
+            ready: HashSet::new(),
+            game: None,
+        });
+        code
+    }
+
+    fn start_game(&mut self, code: &str) {
+        self.next_game_id += 1;
+        let game_id = format!("room-{}", self.next_game_id);
+        let Some(room) = self.rooms.get(code) else {
+            return;
+        };
+        let players = room.members.clone();
+        // 只有快速匹配的对局计积分，玩家创建的房间可随意调整规则
+        let rated = !room.custom;
+        let participants = players
+            .iter()
+            .filter_map(|&id| {
+                let player = self.players.get(&id)?;
+                Some((id, Participant { identity: player.identity()?, name: player.display_name(id) }))
+            })
+            .collect();
+        let Some(room) = self.rooms.get_mut(code) else {
+            return;
+        };
+        let mut engine = Engine::new(EngineConfig::from_settings(&room.settings), seed());
+        let mut state = engine.new_game(&game_id, &players);
+        state.server_time_ms = now_ms();
+        for snake in &mut state.snakes {
+            if let Some(player) = self.players.get(&snake.id) {
+                snake.profile = player.profile.clone();
+            }
+        }
+        room.game = Some(Game {
+            id: game_id.clone(),
+            players: players.clone(),
+            engine,
+            state: state.clone(),
+            sent: state.clone(),
+            input_queues: HashMap::new(),
+            participants,
+            max_lengths: state.snakes.iter().map(|s| (s.id, s.body.len())).collect(),
+            pending_events: vec![],
+            rated,
+            started_at: state.server_time_ms,
+        });
+
+        for &id in &players {
+            self.send(id, GameMessage::Welcome { player_id: id, room_id: game_id.clone() });
+            self.send(id, GameMessage::GameState(state.clone()));
+        }
+        self.broadcast_room(code);
+    }
+
+    /// 房间内当前对局的ID与 tick 间隔（来自引擎配置）
+    pub fn current_game(&self, code: &str) -> Option<(String, Duration)> {
+        let game = self.rooms.get(code)?.game.as_ref()?;
+        let period = Duration::from_millis(game.engine.config().tick_interval_ms as u64);
+        Some((game.id.clone(), period))
+    }
+
+    fn player_room(&self, id: usize) -> Option<&Room> {
+        let code = self.players.get(&id)?.room.as_ref()?;
+        self.rooms.get(code)
+    }
+
+    fn player_game_mut(&mut self, id: usize) -> Option<&mut Game> {
+        let code = self.players.get(&id)?.room.as_ref()?;
+        self.rooms.get_mut(code)?.game.as_mut()
+    }
+
+    /// 是否正在参与一局对局
+    fn in_game(&self, id: usize) -> bool {
+        self.player_room(id)
+            .and_then(|room| room.game.as_ref())
+            .is_some_and(|game| game.players.contains(&id))
+    }
+
+    fn leave_queue(&mut self, id: usize) {
+        if self.queue.iter().any(|q| q.id == id) {
+            self.queue.retain(|q| q.id != id);
+            self.broadcast_matching_status();
+        }
+    }
+
+    /// 玩家的积分标识（查询个人战绩用），未标识时为 None
+    pub fn identity_of(&self, id: usize) -> Option<String> {
+        self.players.get(&id).and_then(Player::identity)
+    }
+
+    /// 玩家当前积分，未标识的玩家按初始积分
+    fn rating_of(&self, id: usize) -> f64 {
+        self.players
+            .get(&id)
+            .and_then(Player::identity)
+            .map(|key| self.ratings.get(&key).elo)
+            .unwrap_or(crate::ratings::INITIAL_RATING)
+    }
+
+    fn send_rating(&self, id: usize) {
+        let Some(key) = self.players.get(&id).and_then(Player::identity) else {
+            return;
+        };
+        let rating = self.ratings.get(&key);
+        self.send(id, GameMessage::Rating {
+            rating: rating.elo.round().max(0.0) as u32,
+            games: rating.games,
+        });
+    }
+
+    /// 向玩家创建的房间内所有成员推送房间信息
+    fn broadcast_room(&self, code: &str) {
+        let Some(room) = self.rooms.get(code).filter(|room| room.custom) else {
+            return;
+        };
+        let details = room.details(&self.players);
+        for &id in &room.members {
+            self.send(id, GameMessage::RoomUpdate(details.clone()));
+        }
+    }
+
+    fn matching_status(&self) -> GameMessage {
+        GameMessage::MatchingStatus {
+            current: self.queue.len(),
+            required: REQUIRED_PLAYERS,
+        }
+    }
+
+    fn broadcast_matching_status(&self) {
+        for q in &self.queue {
+            self.send(q.id, self.matching_status());
+        }
+    }
+
+    fn send(&self, id: usize, msg: GameMessage) {
+        if let Some(tx) = self.players.get(&id).and_then(|p| p.tx.as_ref()) {
+            // 接收端已关闭说明连接正在断开，忽略即可
+            let _ = tx.send(msg);
+        }
+    }
+}
+
+/// 为房间当前的对局启动 tick 循环，对局结束后任务自动退出
+pub fn spawn_room(lobby: SharedLobby, code: String) {
+    tokio::spawn(async move {
+        let Some((game_id, period)) = lobby.lock().unwrap().current_game(&code) else {
+            return;
+        };
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if !lobby.lock().unwrap().tick_room(&code, &game_id) {
+                break;
+            }
+        }
+    });
+}
+
+/// 定时撮合任务：等待中的玩家积分窗口随时间放宽，需要定期重新尝试配对
+pub fn spawn_matchmaker(lobby: SharedLobby) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(MATCHMAKING_INTERVAL);
+        loop {
+            interval.tick().await;
+            let started = lobby.lock().unwrap().match_queue();
+            for code in started {
+                spawn_room(lobby.clone(), code);
+            }
+        }
+    });
+}
+
+/// 匹配阶段的占位状态（`game_started = false` 时前端显示匹配面板）
+fn waiting_state() -> GameState {
+    GameState {
+        room_id: String::new(),
+        snakes: vec![],
+        foods: vec![],
+        game_started: false,
+        game_over: false,
+        tick: 0,
+        server_time_ms: now_ms(),
+        tick_interval_ms: 0,
+        map_width: engine::DEFAULT_MAP_WIDTH,
+        map_height: engine::DEFAULT_MAP_HEIGHT,
+        walls: vec![],
+        wrap: false,
+        events: vec![],
+        input_queue_depth: EngineConfig::default().input_queue_depth,
+    }
+}
+
+fn room_error(message: &str) -> GameMessage {
+    GameMessage::RoomError { message: message.to_string() }
+}
+
+/// 32 位随机字母数字令牌
+fn session_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+/// 6 位大写房间码，便于口头或聊天分享
+fn room_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..ROOM_CODE_LEN)
+        .map(|_| ROOM_CODE_CHARS[rng.gen_range(0..ROOM_CODE_CHARS.len())] as char)
+        .collect()
+}
+
+/// 当前服务端时间（Unix 毫秒）
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    fn connect(lobby: &mut Lobby) -> (usize, UnboundedReceiver<GameMessage>) {
+        let (tx, rx) = unbounded_channel();
+        (lobby.connect(tx), rx)
+    }
+
+    /// 按客户端的方式处理收到的消息：完整状态直接替换，增量应用到当前状态上
+    fn client_state(rx: &mut UnboundedReceiver<GameMessage>, mut state: Option<GameState>) -> Option<GameState> {
+        while let Ok(msg) = rx.try_recv() {
+            match msg {
+                GameMessage::GameState(full) => state = Some(full),
+                GameMessage::StateDelta(change) => {
+                    state = Some(delta::apply(state.as_ref()?, &change).expect("delta base mismatch"));
+                }
+                _ => {}
+            }
+        }
+        state
+    }
+
+    #[test]
+    fn deltas_carry_eliminations_between_ticks() {
+        let mut lobby = Lobby::default();
+        let (a, mut rx) = connect(&mut lobby);
+        let (b, _rx_b) = connect(&mut lobby);
+        let (c, _rx_c) = connect(&mut lobby);
+        lobby.create_room(a, RoomSettings::default());
+        let code = lobby.players[&a].room.clone().unwrap();
+        lobby.join_room(b, &code);
+        lobby.join_room(c, &code);
+        for id in [a, b, c] {
+            lobby.ready(id);
+        }
+        let game_id = lobby.current_game(&code).unwrap().0;
+        let start = client_state(&mut rx, None);
+        assert_eq!(start.as_ref().map(|s| s.room_id.as_str()), Some(game_id.as_str()));
+
+        // c 在两个 tick 之间离开，蛇立即判负
+        lobby.leave_room(c);
+        assert!(lobby.tick_room(&code, &game_id));
+        let client = client_state(&mut rx, start).unwrap();
+        let server = &lobby.rooms[&code].game.as_ref().unwrap().state;
+        assert_eq!(&client, server);
+        let snake = client.snakes.iter().find(|s| s.id == c).unwrap();
+        assert_eq!((snake.alive, snake.death), (false, Some(DeathCause::Left)));
+    }
+}
@@ -0,0 +1,246 @@
+// bin/server/main.rs
+// 多人贪吃蛇参考服务端：在 /ws 上提供与 WsClient 相同的 GameMessage 协议
+mod accounts;
+mod db;
+mod lobby;
+mod ratings;
+mod results;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::mpsc;
+use snake_game::codec::{self, Codec, Frame};
+use snake_game::types::{GameMessage, GameMode, Leaderboard, LeaderboardPeriod, PlayerStats};
+use accounts::{AccountStore, SharedAccounts};
+use lobby::{Lobby, SharedLobby};
+use ratings::RatingStore;
+use results::{ResultStore, SharedResults};
+
+/// 连接处理共享的状态：大厅（内存）与账号库、对局记录（SQLite，单独加锁以免阻塞对局）
+#[derive(Clone)]
+struct AppState {
+    lobby: SharedLobby,
+    accounts: SharedAccounts,
+    results: SharedResults,
+}
+
+#[tokio::main]
+async fn main() {
+    // 监听地址可通过环境变量覆盖，默认与线上部署一致
+    let addr = std::env::var("SNAKE_SERVER_ADDR").unwrap_or_else(|_| "0.0.0.0:3000".to_string());
+    let accounts = AccountStore::shared().unwrap_or_else(|e| panic!("Failed to open account database: {}", e));
+    let results = ResultStore::shared().unwrap_or_else(|e| panic!("Failed to open match database: {}", e));
+    let ratings = RatingStore::open().unwrap_or_else(|e| panic!("Failed to open rating database: {}", e));
+    let lobby = Lobby::shared(ratings, results::spawn_writer(results.clone()));
+    lobby::spawn_matchmaker(lobby.clone());
+    let app = Router::new()
+        .route("/ws", get(ws_handler))
+        .with_state(AppState { lobby, accounts, results });
+
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to bind {}: {}", addr, e));
+    println!("Snake server listening on ws://{}/ws", addr);
+    axum::serve(listener, app).await.expect("Server error");
+}
+
+async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> impl IntoResponse {
+    // 按偏好顺序选择客户端提供的子协议；未提供子协议的客户端使用 JSON
+    ws.protocols(codec::PROTOCOLS)
+        .on_upgrade(move |socket| handle_socket(socket, state))
+}
+
+/// 单个连接的生命周期：发送任务负责序列化出站消息，当前任务负责解析入站消息
+async fn handle_socket(socket: WebSocket, state: AppState) {
+    let lobby = state.lobby.clone();
+    let codec = socket
+        .protocol()
+        .and_then(|p| p.to_str().ok())
+        .map(Codec::from_protocol)
+        .unwrap_or_default();
+    let (mut sink, mut stream) = socket.split();
+    let (tx, mut rx) = mpsc::unbounded_channel::<GameMessage>();
+    // 恢复会话后 player_id 会切换为原玩家ID
+    let mut player_id = lobby.lock().unwrap().connect(tx.clone());
+
+    let send_task = tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            let frame = match codec.encode(&msg) {
+                Ok(Frame::Text(text)) => Message::Text(text.into()),
+                Ok(Frame::Binary(bytes)) => Message::Binary(bytes.into()),
+                Err(e) => {
+                    eprintln!("Failed to serialize message: {}", e);
+                    continue;
+                }
+            };
+            if sink.send(frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    // 入站帧按类型解析：文本为 JSON，二进制为 MessagePack
+    while let Some(Ok(frame)) = stream.next().await {
+        let parsed = match frame {
+            Message::Text(text) => codec::decode_text(&text),
+            Message::Binary(bytes) => codec::decode_binary(&bytes),
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        match parsed {
+            Ok(GameMessage::Resume { token }) => {
+                if let Some(id) = lobby.lock().unwrap().resume(player_id, &token) {
+                    println!("Player {} resumed session as player {}", player_id, id);
+                    player_id = id;
+                }
+            }
+            // Ping 直接在连接上应答，不经过大厅锁
+            Ok(GameMessage::Ping { client_time }) => {
+                let _ = tx.send(GameMessage::Pong { client_time, server_time_ms: lobby::now_ms() });
+            }
+            // 查询与账号操作涉及数据库读写（及密码哈希），放到阻塞线程池执行
+            Ok(GameMessage::GetLeaderboard { period, mode }) => {
+                send_leaderboard(&state, player_id, &tx, period, mode).await;
+            }
+            Ok(GameMessage::GetStats) => {
+                send_stats(&state, player_id, &tx).await;
+            }
+            Ok(msg @ (GameMessage::Register { .. }
+            | GameMessage::Login { .. }
+            | GameMessage::Authenticate { .. }
+            | GameMessage::Logout)) => {
+                handle_account(&state, player_id, &tx, msg).await;
+            }
+            Ok(msg) => handle_message(&lobby, player_id, msg),
+            Err(e) => eprintln!("Player {} sent invalid message: {}", player_id, e),
+        }
+    }
+
+    lobby.lock().unwrap().disconnect(player_id, &tx);
+    send_task.abort();
+}
+
+/// 注册、登录、自动登录与退出登录
+async fn handle_account(state: &AppState, player_id: usize, tx: &mpsc::UnboundedSender<GameMessage>, msg: GameMessage) {
+    let accounts = state.accounts.clone();
+    // 退出登录：先解绑账号（同时通知客户端），再注销令牌
+    if let GameMessage::Logout = msg {
+        let Some(token) = state.lobby.lock().unwrap().logout(player_id) else {
+            return;
+        };
+        let _ = tokio::task::spawn_blocking(move || accounts.lock().unwrap().logout(&token)).await;
+        return;
+    }
+
+    let result = tokio::task::spawn_blocking(move || {
+        let mut accounts = accounts.lock().unwrap();
+        match msg {
+            GameMessage::Register { username, password } => accounts.register(&username, &password).map(Some),
+            GameMessage::Login { username, password } => accounts.login(&username, &password).map(Some),
+            GameMessage::Authenticate { token } => {
+                accounts.authenticate(&token).map(|account| account.map(|account| (account, token)))
+            }
+            _ => Ok(None),
+        }
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some((account, token)))) => {
+            println!("Player {} logged in as {}", player_id, account.username);
+            state.lobby.lock().unwrap().login(player_id, account, token);
+        }
+        // 自动登录的令牌无效或已过期：解绑账号并通知客户端清除保存的令牌
+        Ok(Ok(None)) => {
+            if state.lobby.lock().unwrap().logout(player_id).is_none() {
+                let _ = tx.send(GameMessage::LoggedOut);
+            }
+        }
+        Ok(Err(message)) => {
+            let _ = tx.send(GameMessage::AuthError { message });
+        }
+        Err(e) => eprintln!("Account task for player {} failed: {}", player_id, e),
+    }
+}
+
+/// 查询排行榜（在阻塞线程池中读数据库）
+async fn send_leaderboard(
+    state: &AppState,
+    player_id: usize,
+    tx: &mpsc::UnboundedSender<GameMessage>,
+    period: LeaderboardPeriod,
+    mode: Option<GameMode>,
+) {
+    let results = state.results.clone();
+    let entries = tokio::task::spawn_blocking(move || {
+        results.lock().unwrap().leaderboard(period, mode, lobby::now_ms())
+    })
+    .await;
+    match entries {
+        Ok(Ok(entries)) => {
+            let _ = tx.send(GameMessage::Leaderboard(Leaderboard { period, mode, entries }));
+        }
+        Ok(Err(e)) => eprintln!("Leaderboard query for player {} failed: {}", player_id, e),
+        Err(e) => eprintln!("Leaderboard task for player {} failed: {}", player_id, e),
+    }
+}
+
+/// 查询个人战绩；没有玩家标识（未登录也未发送本地标识）时返回空战绩
+async fn send_stats(state: &AppState, player_id: usize, tx: &mpsc::UnboundedSender<GameMessage>) {
+    let Some(identity) = state.lobby.lock().unwrap().identity_of(player_id) else {
+        let _ = tx.send(GameMessage::PlayerStats(PlayerStats::default()));
+        return;
+    };
+    let results = state.results.clone();
+    let stats = tokio::task::spawn_blocking(move || results.lock().unwrap().stats(&identity)).await;
+    match stats {
+        Ok(Ok(stats)) => {
+            let _ = tx.send(GameMessage::PlayerStats(stats));
+        }
+        Ok(Err(e)) => eprintln!("Stats query for player {} failed: {}", player_id, e),
+        Err(e) => eprintln!("Stats task for player {} failed: {}", player_id, e),
+    }
+}
+
+fn handle_message(lobby: &SharedLobby, player_id: usize, msg: GameMessage) {
+    match msg {
+        GameMessage::Ready => {
+            let started = lobby.lock().unwrap().ready(player_id);
+            for code in started {
+                lobby::spawn_room(lobby.clone(), code);
+            }
+        }
+        GameMessage::PlayerInput(direction) => {
+            lobby.lock().unwrap().input(player_id, direction, None);
+        }
+        GameMessage::SequencedInput { seq, direction } => {
+            lobby.lock().unwrap().input(player_id, direction, Some(seq));
+        }
+        GameMessage::RequestSnapshot => {
+            lobby.lock().unwrap().snapshot(player_id);
+        }
+        GameMessage::ListRooms => {
+            lobby.lock().unwrap().list_rooms(player_id);
+        }
+        GameMessage::CreateRoom { settings } => {
+            lobby.lock().unwrap().create_room(player_id, settings);
+        }
+        GameMessage::JoinRoom { code } => {
+            lobby.lock().unwrap().join_room(player_id, &code);
+        }
+        GameMessage::LeaveRoom => {
+            lobby.lock().unwrap().leave_room(player_id);
+        }
+        GameMessage::Identify { key } => {
+            lobby.lock().unwrap().identify(player_id, &key);
+        }
+        GameMessage::SetProfile { profile } => {
+            lobby.lock().unwrap().set_profile(player_id, profile);
+        }
+        other => eprintln!("Player {} sent unexpected message: {:?}", player_id, other),
+    }
+}
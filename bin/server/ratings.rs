@@ -0,0 +1,185 @@
+// bin/server/ratings.rs
+// 玩家积分（Elo）：按玩家标识保存在 SQLite 中，快速匹配对局结束后根据排名更新
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use rusqlite::{params, Connection};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use crate::db;
+
+/// 新玩家的初始积分
+pub const INITIAL_RATING: f64 = 1500.0;
+/// 两人对局时单局的最大积分变化；多人对局按对手数平摊
+const K_FACTOR: f64 = 32.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Rating {
+    pub elo: f64,
+    // 已计分的对局数
+    pub games: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self { elo: INITIAL_RATING, games: 0 }
+    }
+}
+
+/// 全部玩家的积分：读取走内存中的副本（在大厅锁内使用），修改经通道交给后台任务写入数据库
+#[derive(Default)]
+pub struct RatingStore {
+    ratings: HashMap<String, Rating>,
+    // 未打开数据库时只保存在内存中
+    writer: Option<UnboundedSender<Vec<(String, Rating)>>>,
+}
+
+impl RatingStore {
+    /// 打开数据库、建表并加载全部积分，同时启动后台写入任务
+    pub fn open() -> rusqlite::Result<Self> {
+        let conn = db::open()?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS ratings (
+                identity TEXT PRIMARY KEY,
+                elo REAL NOT NULL,
+                games INTEGER NOT NULL
+            );",
+        )?;
+        let ratings = conn
+            .prepare("SELECT identity, elo, games FROM ratings")?
+            .query_map([], |row| Ok((row.get(0)?, Rating { elo: row.get(1)?, games: row.get(2)? })))?
+            .collect::<rusqlite::Result<HashMap<String, Rating>>>()?;
+        Ok(Self { ratings, writer: Some(spawn_writer(conn)) })
+    }
+
+    pub fn get(&self, key: &str) -> Rating {
+        self.ratings.get(key).copied().unwrap_or_default()
+    }
+
+    /// 按一局的结果更新积分，`results` 为（玩家标识, 名次），名次从 0 开始、并列时相同。
+    ///
+    /// 多人对局拆成两两对局计算：名次靠前者胜，名次相同为平局；
+    /// 每对的变化为 `K / (人数 - 1) * (实际 - 期望)`，总变化与两人对局同一量级。
+    pub fn record(&mut self, results: &[(String, u32)]) {
+        if results.len() < 2 {
+            return;
+        }
+        let k = K_FACTOR / (results.len() - 1) as f64;
+        let before: Vec<f64> = results.iter().map(|(key, _)| self.get(key).elo).collect();
+        for (i, (key, place)) in results.iter().enumerate() {
+            let delta: f64 = results
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(j, (_, other))| {
+                    let actual = match place.cmp(other) {
+                        std::cmp::Ordering::Less => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Greater => 0.0,
+                    };
+                    k * (actual - expected(before[i], before[j]))
+                })
+                .sum();
+            let rating = self.ratings.entry(key.clone()).or_default();
+            rating.elo += delta;
+            rating.games += 1;
+        }
+        if let Some(writer) = &self.writer {
+            let changed = results.iter().map(|(key, _)| (key.clone(), self.get(key))).collect();
+            let _ = writer.send(changed);
+        }
+    }
+}
+
+/// 后台写入任务：按收到的顺序把变化的积分写入数据库，不占用大厅锁
+fn spawn_writer(conn: Connection) -> UnboundedSender<Vec<(String, Rating)>> {
+    let conn = Arc::new(Mutex::new(conn));
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<(String, Rating)>>();
+    tokio::spawn(async move {
+        while let Some(changed) = rx.recv().await {
+            let conn = conn.clone();
+            let written = tokio::task::spawn_blocking(move || save(&mut conn.lock().unwrap(), &changed)).await;
+            match written {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => eprintln!("Failed to save ratings: {}", e),
+                Err(e) => eprintln!("Rating save task failed: {}", e),
+            }
+        }
+    });
+    tx
+}
+
+/// 在一个事务中写入一局涉及的全部积分
+fn save(conn: &mut Connection, changed: &[(String, Rating)]) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    for (key, rating) in changed {
+        tx.execute(
+            "INSERT INTO ratings (identity, elo, games) VALUES (?1, ?2, ?3)
+             ON CONFLICT(identity) DO UPDATE SET elo = excluded.elo, games = excluded.games",
+            params![key, rating.elo, rating.games],
+        )?;
+    }
+    tx.commit()
+}
+
+/// 积分为 `rating` 的玩家战胜积分为 `opponent` 的玩家的期望概率
+fn expected(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(store: &mut RatingStore, results: &[(&str, u32)]) {
+        let results: Vec<(String, u32)> =
+            results.iter().map(|&(key, place)| (key.to_string(), place)).collect();
+        store.record(&results);
+    }
+
+    fn assert_elo(store: &RatingStore, key: &str, elo: f64) {
+        let actual = store.get(key).elo;
+        assert!((actual - elo).abs() < 1e-9, "{}: {} != {}", key, actual, elo);
+    }
+
+    #[test]
+    fn two_player_game() {
+        let mut store = RatingStore::default();
+        record(&mut store, &[("a", 0), ("b", 1)]);
+        // 积分相同，期望为 0.5
+        assert_elo(&store, "a", INITIAL_RATING + K_FACTOR / 2.0);
+        assert_elo(&store, "b", INITIAL_RATING - K_FACTOR / 2.0);
+        assert_eq!((store.get("a").games, store.get("b").games), (1, 1));
+
+        // 积分高者再次获胜，涨得更少；两人的变化相抵
+        record(&mut store, &[("b", 1), ("a", 0)]);
+        let gain = store.get("a").elo - (INITIAL_RATING + K_FACTOR / 2.0);
+        assert!(gain > 0.0 && gain < K_FACTOR / 2.0);
+        assert_elo(&store, "b", INITIAL_RATING - K_FACTOR / 2.0 - gain);
+        assert_eq!(store.get("a").games, 2);
+    }
+
+    #[test]
+    fn multi_player_game_with_ties() {
+        let mut store = RatingStore::default();
+        // 四人对局，第二名并列：每对的变化为 K / 3 * (实际 - 0.5)
+        record(&mut store, &[("a", 0), ("b", 1), ("c", 1), ("d", 3)]);
+        let k = K_FACTOR / 3.0;
+        assert_elo(&store, "a", INITIAL_RATING + 1.5 * k);
+        assert_elo(&store, "b", INITIAL_RATING);
+        assert_elo(&store, "c", INITIAL_RATING);
+        assert_elo(&store, "d", INITIAL_RATING - 1.5 * k);
+
+        // 三人对局，第一名并列
+        record(&mut store, &[("e", 0), ("f", 0), ("g", 2)]);
+        assert_elo(&store, "e", INITIAL_RATING + K_FACTOR / 4.0);
+        assert_elo(&store, "f", INITIAL_RATING + K_FACTOR / 4.0);
+        assert_elo(&store, "g", INITIAL_RATING - K_FACTOR / 2.0);
+    }
+
+    #[test]
+    fn single_player_is_not_rated() {
+        let mut store = RatingStore::default();
+        record(&mut store, &[("a", 0)]);
+        assert_eq!(store.get("a").games, 0);
+        assert_elo(&store, "a", INITIAL_RATING);
+    }
+}
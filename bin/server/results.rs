@@ -0,0 +1,389 @@
+// bin/server/results.rs
+// 对局记录：每局结束后由后台任务写入 SQLite，供排行榜与个人战绩查询
+use std::sync::{Arc, Mutex};
+use rusqlite::{params, Connection};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use snake_game::types::{
+    DeathCause, GameMode, LeaderboardEntry, LeaderboardPeriod, MatchParticipant, MatchSummary, PlayerStats,
+};
+use crate::db;
+
+/// 排行榜最多返回的人数
+pub const LEADERBOARD_SIZE: usize = 50;
+/// 个人战绩中列出的最近对局数
+pub const RECENT_MATCHES: usize = 20;
+const DAY_MS: u64 = 24 * 3600 * 1000;
+
+pub type SharedResults = Arc<Mutex<ResultStore>>;
+
+/// 一局的结果（只包含有玩家标识的参与者）
+#[derive(Debug, Clone)]
+pub struct MatchRecord {
+    pub mode: GameMode,
+    // 是否为计积分的快速匹配对局
+    pub rated: bool,
+    pub started_at_ms: u64,
+    pub ended_at_ms: u64,
+    // 参与对局的蛇数，包括没有玩家标识的参与者（用于区分单人与多人对局）
+    pub total_players: u32,
+    pub players: Vec<MatchPlayer>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchPlayer {
+    pub identity: String,
+    pub name: String,
+    pub score: u32,
+    // 名次，从 0 开始，并列时相同
+    pub place: u32,
+    // 对局中达到的最大长度
+    pub max_length: u32,
+    // 存活到最后时为 None
+    pub death: Option<DeathCause>,
+}
+
+pub struct ResultStore {
+    conn: Connection,
+}
+
+impl ResultStore {
+    /// 打开数据库并建表
+    pub fn open() -> rusqlite::Result<Self> {
+        let conn = db::open()?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS matches (
+                id INTEGER PRIMARY KEY,
+                mode TEXT NOT NULL,
+                rated INTEGER NOT NULL,
+                players INTEGER NOT NULL,
+                started_at INTEGER NOT NULL,
+                ended_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS matches_ended_at ON matches(ended_at);
+            CREATE TABLE IF NOT EXISTS match_players (
+                match_id INTEGER NOT NULL REFERENCES matches(id),
+                identity TEXT NOT NULL,
+                name TEXT NOT NULL,
+                score INTEGER NOT NULL,
+                place INTEGER NOT NULL,
+                max_length INTEGER NOT NULL DEFAULT 0,
+                death TEXT
+            );
+            CREATE INDEX IF NOT EXISTS match_players_identity ON match_players(identity);",
+        )?;
+        // 旧数据库中的对局没有这两项，最大长度记为 0，死因记为空
+        db::ensure_column(&conn, "match_players", "max_length", "INTEGER NOT NULL DEFAULT 0")?;
+        db::ensure_column(&conn, "match_players", "death", "TEXT")?;
+        Ok(Self { conn })
+    }
+
+    pub fn shared() -> rusqlite::Result<SharedResults> {
+        Ok(Arc::new(Mutex::new(Self::open()?)))
+    }
+
+    /// 写入一局的结果
+    pub fn record(&mut self, record: &MatchRecord) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO matches (mode, rated, players, started_at, ended_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                mode_key(record.mode),
+                record.rated,
+                record.total_players,
+                record.started_at_ms as i64,
+                record.ended_at_ms as i64,
+            ],
+        )?;
+        let match_id = tx.last_insert_rowid();
+        for player in &record.players {
+            tx.execute(
+                "INSERT INTO match_players (match_id, identity, name, score, place, max_length, death)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    match_id,
+                    player.identity,
+                    player.name,
+                    player.score,
+                    player.place,
+                    player.max_length,
+                    player.death.map(death_key),
+                ],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// 排行榜：统计周期内（可按玩法筛选）各玩家在快速匹配对局中的胜场、最高分与总分。
+    /// 只有多人对局的第一名计为胜场；自建房间的对局不计入
+    pub fn leaderboard(
+        &self,
+        period: LeaderboardPeriod,
+        mode: Option<GameMode>,
+        now_ms: u64,
+    ) -> rusqlite::Result<Vec<LeaderboardEntry>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT
+                (SELECT latest.name FROM match_players latest
+                 WHERE latest.identity = mp.identity ORDER BY latest.match_id DESC LIMIT 1),
+                COUNT(*),
+                SUM(mp.place = 0 AND m.players > 1),
+                MAX(mp.score),
+                SUM(mp.score)
+             FROM match_players mp JOIN matches m ON m.id = mp.match_id
+             WHERE m.rated = 1 AND m.ended_at >= ?1 AND (?2 IS NULL OR m.mode = ?2)
+             GROUP BY mp.identity
+             ORDER BY 3 DESC, 4 DESC, 5 DESC
+             LIMIT ?3",
+        )?;
+        let since = period_start_ms(period, now_ms) as i64;
+        let rows = stmt.query_map(
+            params![since, mode.map(mode_key), LEADERBOARD_SIZE as i64],
+            |row| {
+                Ok(LeaderboardEntry {
+                    rank: 0,
+                    name: row.get(0)?,
+                    games: row.get(1)?,
+                    wins: row.get(2)?,
+                    best_score: row.get(3)?,
+                    total_score: row.get::<_, i64>(4)? as u64,
+                })
+            },
+        )?;
+        let mut entries = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        for (i, entry) in entries.iter_mut().enumerate() {
+            entry.rank = i + 1;
+        }
+        Ok(entries)
+    }
+
+    /// 个人战绩：所有对局的汇总，以及最近 `RECENT_MATCHES` 局的详情
+    pub fn stats(&self, identity: &str) -> rusqlite::Result<PlayerStats> {
+        let mut stats = self.conn.query_row(
+            "SELECT
+                COUNT(*),
+                COALESCE(SUM(m.players > 1), 0),
+                COALESCE(SUM(mp.place = 0 AND m.players > 1), 0),
+                COALESCE(SUM(mp.score), 0),
+                COALESCE(MAX(mp.score), 0),
+                COALESCE(MAX(mp.max_length), 0)
+             FROM match_players mp JOIN matches m ON m.id = mp.match_id
+             WHERE mp.identity = ?1",
+            params![identity],
+            |row| {
+                Ok(PlayerStats {
+                    games: row.get(0)?,
+                    multiplayer_games: row.get(1)?,
+                    wins: row.get(2)?,
+                    total_score: row.get::<_, i64>(3)? as u64,
+                    best_score: row.get(4)?,
+                    longest: row.get(5)?,
+                    recent: Vec::new(),
+                })
+            },
+        )?;
+
+        let mut matches = self.conn.prepare_cached(
+            "SELECT m.id, m.mode, m.players, m.started_at, m.ended_at
+             FROM match_players mp JOIN matches m ON m.id = mp.match_id
+             WHERE mp.identity = ?1
+             ORDER BY m.id DESC
+             LIMIT ?2",
+        )?;
+        let rows = matches.query_map(params![identity, RECENT_MATCHES as i64], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })?;
+        let mut participants = self.conn.prepare_cached(
+            "SELECT identity, name, score, place, max_length, death FROM match_players
+             WHERE match_id = ?1
+             ORDER BY place, rowid",
+        )?;
+        for row in rows {
+            let (match_id, mode, total_players, started_at, ended_at) = row?;
+            let Some(mode) = mode_from_key(&mode) else {
+                continue;
+            };
+            let players = participants
+                .query_map(params![match_id], |row| {
+                    let death: Option<String> = row.get(5)?;
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        MatchParticipant {
+                            name: row.get(1)?,
+                            score: row.get(2)?,
+                            place: row.get(3)?,
+                            max_length: row.get(4)?,
+                            death: death.as_deref().and_then(death_from_key),
+                        },
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let Some(me) = players.iter().find(|(id, _)| id == identity).map(|(_, p)| p.clone()) else {
+                continue;
+            };
+            stats.recent.push(MatchSummary {
+                ended_at_ms: ended_at as u64,
+                duration_ms: (ended_at - started_at).max(0) as u64,
+                mode,
+                players: total_players,
+                me,
+                participants: players.into_iter().map(|(_, p)| p).collect(),
+            });
+        }
+        Ok(stats)
+    }
+}
+
+/// 启动写入任务，返回提交对局记录的通道；写入在阻塞线程池中进行，不占用大厅锁
+pub fn spawn_writer(store: SharedResults) -> UnboundedSender<MatchRecord> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<MatchRecord>();
+    tokio::spawn(async move {
+        while let Some(record) = rx.recv().await {
+            let store = store.clone();
+            let written = tokio::task::spawn_blocking(move || store.lock().unwrap().record(&record)).await;
+            match written {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => eprintln!("Failed to record match: {}", e),
+                Err(e) => eprintln!("Match record task failed: {}", e),
+            }
+        }
+    });
+    tx
+}
+
+fn mode_key(mode: GameMode) -> &'static str {
+    match mode {
+        GameMode::LastAlive => "last_alive",
+        GameMode::TargetScore => "target_score",
+        GameMode::TimeLimit => "time_limit",
+    }
+}
+
+fn mode_from_key(key: &str) -> Option<GameMode> {
+    GameMode::ALL.into_iter().find(|&mode| mode_key(mode) == key)
+}
+
+fn death_key(cause: DeathCause) -> &'static str {
+    match cause {
+        DeathCause::Wall => "wall",
+        DeathCause::OwnBody => "own_body",
+        DeathCause::OtherSnake => "other_snake",
+        DeathCause::Left => "left",
+        DeathCause::Disconnected => "disconnected",
+    }
+}
+
+fn death_from_key(key: &str) -> Option<DeathCause> {
+    match key {
+        "wall" => Some(DeathCause::Wall),
+        "own_body" => Some(DeathCause::OwnBody),
+        "other_snake" => Some(DeathCause::OtherSnake),
+        "left" => Some(DeathCause::Left),
+        "disconnected" => Some(DeathCause::Disconnected),
+        _ => None,
+    }
+}
+
+/// 统计周期的起点（Unix 毫秒，UTC）：总榜为 0，赛季为本季度第一天，周榜为本周一
+fn period_start_ms(period: LeaderboardPeriod, now_ms: u64) -> u64 {
+    let today = (now_ms / DAY_MS) as i64;
+    let start_day = match period {
+        LeaderboardPeriod::AllTime => return 0,
+        // 1970-01-01 是周四
+        LeaderboardPeriod::Weekly => today - (today + 3).rem_euclid(7),
+        LeaderboardPeriod::Season => {
+            let (year, month, _) = civil_from_days(today);
+            days_from_civil(year, (month - 1) / 3 * 3 + 1, 1)
+        }
+    };
+    start_day.max(0) as u64 * DAY_MS
+}
+
+/// 距 1970-01-01 的天数转为公历 (年, 月, 日)（Howard Hinnant 的 civil_from_days 算法）
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// 公历日期转为距 1970-01-01 的天数（`civil_from_days` 的逆运算）
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01（周一）距 1970-01-01 的天数
+    const JAN_1_2024: u64 = 19_723;
+
+    #[test]
+    fn epoch() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        // 1970-01-01 是周四，所在周的周一早于纪元，取 0
+        assert_eq!(period_start_ms(LeaderboardPeriod::Weekly, 0), 0);
+        assert_eq!(period_start_ms(LeaderboardPeriod::Season, DAY_MS * 10), 0);
+        assert_eq!(period_start_ms(LeaderboardPeriod::AllTime, JAN_1_2024 * DAY_MS), 0);
+    }
+
+    #[test]
+    fn week_starts_on_monday() {
+        let weekly = |ms| period_start_ms(LeaderboardPeriod::Weekly, ms);
+        let monday = JAN_1_2024 * DAY_MS;
+        assert_eq!(weekly(monday), monday);
+        // 周日最后一毫秒仍属于本周，下周一零点开始新的一周
+        assert_eq!(weekly(monday + 7 * DAY_MS - 1), monday);
+        assert_eq!(weekly(monday + 7 * DAY_MS), monday + 7 * DAY_MS);
+        // 前一天（周日）属于上一周
+        assert_eq!(weekly(monday - 1), monday - 7 * DAY_MS);
+    }
+
+    #[test]
+    fn leap_days() {
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19_783), (2024, 3, 1));
+        assert_eq!(days_from_civil(2024, 2, 29), 19_782);
+        assert_eq!(days_from_civil(2000, 2, 29), 11_016);
+        // 2100 年不是闰年
+        assert_eq!(civil_from_days(47_540), (2100, 2, 28));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+        for days in [0, 11_016, 19_782, 47_540, 47_541] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn season_rolls_over_with_the_quarter_and_year() {
+        let season = |days: u64, ms_into_day: u64| {
+            period_start_ms(LeaderboardPeriod::Season, days * DAY_MS + ms_into_day)
+        };
+        // 2023-12-31 的最后一毫秒属于 2023-10-01 开始的赛季
+        assert_eq!(season(JAN_1_2024 - 1, DAY_MS - 1), 19_631 * DAY_MS);
+        assert_eq!(season(JAN_1_2024, 0), JAN_1_2024 * DAY_MS);
+        // 闰年的第一季度共 91 天，2024-04-01 开始新赛季
+        assert_eq!(season(19_813, DAY_MS - 1), JAN_1_2024 * DAY_MS);
+        assert_eq!(season(19_814, 0), 19_814 * DAY_MS);
+    }
+}
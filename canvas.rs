@@ -0,0 +1,141 @@
+// canvas.rs
+// Canvas 2D 渲染：与 DOM 渲染（GameMap）显示同样的内容，但每帧只绘制一个 <canvas>，
+// 大地图、多玩家时不再产生成百上千个 div
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use yew::prelude::*;
+use crate::game::{board_style, darken_color, is_wrap, map_dims, snake_color, snake_label, use_snake_positions, GameMapProps, WALL_COLOR};
+use crate::types::{Food, GameState, Snake};
+
+// 画布分辨率：每格 20 像素，显示尺寸由 CSS 随地图容器缩放
+const CELL: f64 = 20.0;
+
+// ---------------- Canvas 地图组件 ----------------
+#[function_component(GameCanvas)]
+pub fn game_canvas(props: &GameMapProps) -> Html {
+    let GameMapProps { state, my_id, interpolate } = props;
+    let positions = use_snake_positions(state, *interpolate);
+    let canvas_ref = use_node_ref();
+
+    let (game_started, game_over, is_empty) = match state {
+        Some(s) => (s.game_started, s.game_over, s.snakes.is_empty()),
+        None => (false, false, true),
+    };
+    let (width, height) = map_dims(state);
+    let (canvas_width, canvas_height) = (width as f64 * CELL, height as f64 * CELL);
+
+    // 每次渲染后重绘整张画布
+    {
+        let canvas_ref = canvas_ref.clone();
+        let state = state.clone();
+        let my_id = *my_id;
+        use_effect(move || {
+            if let Some(ctx) = context_2d(&canvas_ref) {
+                ctx.clear_rect(0.0, 0.0, canvas_width, canvas_height);
+                draw(&ctx, state.as_ref(), &positions, my_id);
+            }
+            || ()
+        });
+    }
+
+    html! {
+        <div class="game-container">
+            <div class="game-map" style={board_style((width, height), is_wrap(state))}>
+                <canvas
+                    ref={canvas_ref}
+                    width={canvas_width.to_string()}
+                    height={canvas_height.to_string()}
+                    style="display: block; width: 100%; height: 100%;"
+                />
+                // 游戏未开始提示
+                if !game_started && is_empty {
+                    <div class="game-tip">{ "等待玩家加入..." }</div>
+                }
+                // 游戏结束提示（后端控制结束逻辑）
+                if game_over {
+                    <div class="game-over-tip">{ "游戏结束！" }</div>
+                }
+            </div>
+        </div>
+    }
+}
+
+/// 取得画布的 2D 上下文
+fn context_2d(canvas_ref: &NodeRef) -> Option<CanvasRenderingContext2d> {
+    canvas_ref
+        .cast::<HtmlCanvasElement>()?
+        .get_context("2d")
+        .ok()??
+        .dyn_into::<CanvasRenderingContext2d>()
+        .ok()
+}
+
+/// 绘制一帧：障碍物与食物在下，蛇在上，自己的蛇最后绘制以免被遮挡
+fn draw(
+    ctx: &CanvasRenderingContext2d,
+    state: Option<&GameState>,
+    positions: &[Vec<(f64, f64)>],
+    my_id: Option<usize>,
+) {
+    let Some(state) = state else {
+        return;
+    };
+
+    ctx.set_fill_style_str(WALL_COLOR);
+    for wall in &state.walls {
+        ctx.fill_rect(wall.x as f64 * CELL, wall.y as f64 * CELL, CELL, CELL);
+    }
+    for food in &state.foods {
+        draw_food(ctx, food);
+    }
+    let (mine, others): (Vec<_>, Vec<_>) = state
+        .snakes
+        .iter()
+        .zip(positions)
+        .partition(|(snake, _)| Some(snake.id) == my_id);
+    for (snake, body) in others {
+        draw_snake(ctx, snake, body, false);
+    }
+    for (snake, body) in mine {
+        draw_snake(ctx, snake, body, true);
+    }
+}
+
+/// 绘制单条蛇：蛇头用深色，身体用浅色，蛇头上方显示头像与昵称；自己的蛇加描边并标记"我"
+fn draw_snake(ctx: &CanvasRenderingContext2d, snake: &Snake, positions: &[(f64, f64)], is_me: bool) {
+    let color = snake_color(snake);
+    let head_color = darken_color(color.clone());
+    ctx.set_line_width(2.0);
+    ctx.set_stroke_style_str("#222");
+
+    // 从尾到头绘制，保证蛇头在最上层
+    for (idx, (x, y)) in positions.iter().enumerate().rev() {
+        ctx.set_fill_style_str(if idx == 0 { &head_color } else { &color });
+        ctx.fill_rect(x * CELL, y * CELL, CELL, CELL);
+        if is_me {
+            ctx.stroke_rect(x * CELL, y * CELL, CELL, CELL);
+        }
+    }
+
+    if let Some((x, y)) = positions.first() {
+        ctx.set_fill_style_str(if is_me { "#222" } else { "#444" });
+        ctx.set_font(if is_me { "bold 12px Arial, sans-serif" } else { "11px Arial, sans-serif" });
+        ctx.set_text_align("center");
+        let _ = ctx.fill_text(&snake_label(snake, is_me), x * CELL + CELL / 2.0, y * CELL - 4.0);
+    }
+}
+
+/// 绘制单个食物
+fn draw_food(ctx: &CanvasRenderingContext2d, food: &Food) {
+    let (x, y) = (food.position.x as f64, food.position.y as f64);
+    ctx.set_fill_style_str("#ff3333");
+    ctx.begin_path();
+    let _ = ctx.arc(
+        x * CELL + CELL / 2.0,
+        y * CELL + CELL / 2.0,
+        CELL / 2.0,
+        0.0,
+        std::f64::consts::TAU,
+    );
+    ctx.fill();
+}
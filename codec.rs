@@ -0,0 +1,63 @@
+// codec.rs
+// GameMessage 的线上编码：JSON 文本帧或 MessagePack 二进制帧，通过 WebSocket 子协议协商
+use crate::types::GameMessage;
+
+/// JSON 文本帧子协议（未协商子协议的旧客户端同样按 JSON 处理）
+pub const JSON_PROTOCOL: &str = "snake.json";
+/// MessagePack 二进制帧子协议（体积约为 JSON 的一半，适合移动网络）
+pub const MSGPACK_PROTOCOL: &str = "snake.msgpack";
+/// 按偏好顺序排列的子协议，客户端按此顺序提供，服务端按此顺序选择
+pub const PROTOCOLS: [&str; 2] = [MSGPACK_PROTOCOL, JSON_PROTOCOL];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Codec {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+/// 编码后的 WebSocket 帧
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl Codec {
+    /// 根据握手协商出的子协议选择编码，未知或为空时退回 JSON
+    pub fn from_protocol(protocol: &str) -> Self {
+        match protocol {
+            MSGPACK_PROTOCOL => Codec::MessagePack,
+            _ => Codec::Json,
+        }
+    }
+
+    pub fn protocol(&self) -> &'static str {
+        match self {
+            Codec::Json => JSON_PROTOCOL,
+            Codec::MessagePack => MSGPACK_PROTOCOL,
+        }
+    }
+
+    pub fn encode(&self, msg: &GameMessage) -> Result<Frame, String> {
+        match self {
+            Codec::Json => serde_json::to_string(msg)
+                .map(Frame::Text)
+                .map_err(|e| e.to_string()),
+            // 使用带字段名的编码，保证 `#[serde(tag = "type")]` 的内部标签可以正确解析
+            Codec::MessagePack => rmp_serde::to_vec_named(msg)
+                .map(Frame::Binary)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// 解析文本帧（JSON）
+pub fn decode_text(text: &str) -> Result<GameMessage, String> {
+    serde_json::from_str(text).map_err(|e| e.to_string())
+}
+
+/// 解析二进制帧（MessagePack）
+pub fn decode_binary(bytes: &[u8]) -> Result<GameMessage, String> {
+    rmp_serde::from_slice(bytes).map_err(|e| e.to_string())
+}
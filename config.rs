@@ -0,0 +1,127 @@
+// config.rs
+// 运行时解析服务端地址，同一份构建可部署到本地、预发和生产环境；以及房间分享链接、本地玩家标识、资料与登录令牌
+use web_sys::{window, Storage, UrlSearchParams};
+use crate::types::PlayerProfile;
+
+/// URL 查询参数名，例如 `?server=wss://staging.example.com/ws`
+pub const SERVER_QUERY_PARAM: &str = "server";
+/// URL 查询参数名，分享链接 `?room=ABC123` 打开后自动加入该房间
+pub const ROOM_QUERY_PARAM: &str = "room";
+/// localStorage 中保存玩家标识的键名
+pub const PLAYER_KEY_STORAGE: &str = "snake_player_key";
+/// localStorage 中保存玩家资料（JSON）的键名
+pub const PROFILE_STORAGE: &str = "snake_profile";
+/// localStorage 中保存登录令牌的键名
+pub const AUTH_TOKEN_STORAGE: &str = "snake_auth_token";
+/// 服务端 WebSocket 路径
+pub const WS_PATH: &str = "/ws";
+/// 页面不是通过 http(s) 打开时的兜底地址（对应本地运行的参考服务端）
+pub const DEFAULT_SERVER_URL: &str = "ws://localhost:3000/ws";
+
+/// 按优先级解析服务端地址：
+/// 1. URL 查询参数 `server`
+/// 2. 构建时环境变量 `SNAKE_SERVER_URL`（如 `SNAKE_SERVER_URL=wss://... trunk build`）
+/// 3. 与页面同源的地址（https 页面使用 `wss://`）
+/// 4. `DEFAULT_SERVER_URL`
+pub fn server_url() -> String {
+    query_server_url()
+        .or_else(|| option_env!("SNAKE_SERVER_URL").map(str::to_string))
+        .or_else(same_origin_url)
+        .unwrap_or_else(|| DEFAULT_SERVER_URL.to_string())
+}
+
+/// 读取查询参数中的服务端地址
+fn query_server_url() -> Option<String> {
+    query_param(SERVER_QUERY_PARAM)
+}
+
+/// 分享链接中的房间码
+pub fn room_code() -> Option<String> {
+    query_param(ROOM_QUERY_PARAM).map(|code| code.to_uppercase())
+}
+
+/// 房间的分享链接：当前页面地址加上 `?room=` 参数（保留 `server` 参数）
+pub fn room_share_url(code: &str) -> Option<String> {
+    let location = window()?.location();
+    let base = format!("{}{}", location.origin().ok()?, location.pathname().ok()?);
+    let params = UrlSearchParams::new_with_str(&location.search().ok()?).ok()?;
+    params.set(ROOM_QUERY_PARAM, code);
+    Some(format!("{}?{}", base, String::from(params.to_string())))
+}
+
+/// 本浏览器的持久玩家标识（服务端据此保存积分），首次调用时随机生成并写入 localStorage。
+/// localStorage 不可用（如隐私模式）时返回 None
+pub fn player_key() -> Option<String> {
+    let storage = local_storage()?;
+    if let Some(key) = storage.get_item(PLAYER_KEY_STORAGE).ok()?.filter(|k| !k.is_empty()) {
+        return Some(key);
+    }
+    let key: String = (0..32)
+        .map(|_| {
+            let digit = (js_sys::Math::random() * 36.0) as u32;
+            char::from_digit(digit.min(35), 36).unwrap_or('0')
+        })
+        .collect();
+    storage.set_item(PLAYER_KEY_STORAGE, &key).ok()?;
+    Some(key)
+}
+
+/// 上次保存的玩家资料
+pub fn saved_profile() -> Option<PlayerProfile> {
+    let text = local_storage()?.get_item(PROFILE_STORAGE).ok()??;
+    serde_json::from_str(&text).ok()
+}
+
+/// 保存玩家资料（服务端接受后调用），下次打开页面时自动发送
+pub fn save_profile(profile: &PlayerProfile) {
+    let (Some(storage), Ok(text)) = (local_storage(), serde_json::to_string(profile)) else {
+        return;
+    };
+    let _ = storage.set_item(PROFILE_STORAGE, &text);
+}
+
+/// 上次登录时服务端签发的登录令牌
+pub fn auth_token() -> Option<String> {
+    local_storage()?
+        .get_item(AUTH_TOKEN_STORAGE)
+        .ok()?
+        .filter(|token| !token.is_empty())
+}
+
+/// 保存登录令牌；为 None 时清除（退出登录或令牌失效）
+pub fn save_auth_token(token: Option<&str>) {
+    let Some(storage) = local_storage() else {
+        return;
+    };
+    let _ = match token {
+        Some(token) => storage.set_item(AUTH_TOKEN_STORAGE, token),
+        None => storage.remove_item(AUTH_TOKEN_STORAGE),
+    };
+}
+
+fn local_storage() -> Option<Storage> {
+    window()?.local_storage().ok()?
+}
+
+/// 读取非空的查询参数
+fn query_param(name: &str) -> Option<String> {
+    let search = window()?.location().search().ok()?;
+    let params = UrlSearchParams::new_with_str(&search).ok()?;
+    params
+        .get(name)
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// 根据 `window.location` 拼出同源的 WebSocket 地址
+fn same_origin_url() -> Option<String> {
+    let location = window()?.location();
+    let scheme = match location.protocol().ok()?.as_str() {
+        "https:" => "wss",
+        "http:" => "ws",
+        // file:// 等协议没有可用的同源服务端
+        _ => return None,
+    };
+    let host = location.host().ok().filter(|h| !h.is_empty())?;
+    Some(format!("{}://{}{}", scheme, host, WS_PATH))
+}
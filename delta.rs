@@ -0,0 +1,258 @@
+// delta.rs
+// 增量状态的生成（服务端）与应用（客户端）
+use crate::types::{GameState, StateDelta, SnakeDelta, Snake, Food, Position};
+
+/// 计算 `prev` → `next` 的增量；房间、蛇的集合或开局状态变化时无法用增量表示，返回 None
+pub fn diff(prev: &GameState, next: &GameState) -> Option<StateDelta> {
+    let same_shape = prev.room_id == next.room_id
+        && prev.game_started == next.game_started
+        && (prev.map_width, prev.map_height) == (next.map_width, next.map_height)
+        && prev.walls == next.walls
+        && prev.wrap == next.wrap
+        && prev.snakes.len() == next.snakes.len()
+        && prev.snakes.iter().zip(&next.snakes).all(|(a, b)| a.id == b.id);
+    if !same_shape {
+        return None;
+    }
+
+    let snakes = prev
+        .snakes
+        .iter()
+        .zip(&next.snakes)
+        .filter(|(old, new)| old != new)
+        .map(|(old, new)| diff_snake(old, new))
+        .collect();
+
+    Some(StateDelta {
+        base_tick: prev.tick,
+        tick: next.tick,
+        server_time_ms: next.server_time_ms,
+        snakes,
+        foods_eaten: positions_missing(&prev.foods, &next.foods),
+        foods_spawned: positions_missing(&next.foods, &prev.foods),
+        game_over: next.game_over,
+        events: next.events.clone(),
+    })
+}
+
+/// 把增量应用到 tick 为 `base_tick` 的状态上；tick 不匹配（丢帧或乱序）时返回 None
+pub fn apply(state: &GameState, delta: &StateDelta) -> Option<GameState> {
+    if state.tick != delta.base_tick {
+        return None;
+    }
+
+    let mut next = state.clone();
+    for change in &delta.snakes {
+        let snake = next.snakes.iter_mut().find(|s| s.id == change.id)?;
+        if change.tail_pops > snake.body.len() {
+            return None;
+        }
+        let keep = snake.body.len() - change.tail_pops;
+        snake.body.truncate(keep);
+        for head in &change.head_pushes {
+            snake.body.insert(0, *head);
+        }
+        snake.direction = change.direction;
+        snake.alive = change.alive;
+        snake.score = change.score;
+        snake.last_input_seq = change.last_input_seq;
+        snake.death = change.death;
+    }
+
+    next.foods.retain(|f| !delta.foods_eaten.contains(&f.position));
+    next.foods.extend(delta.foods_spawned.iter().map(|&position| Food { position }));
+    next.game_over = delta.game_over;
+    next.events = delta.events.clone();
+    next.tick = delta.tick;
+    next.server_time_ms = delta.server_time_ms;
+    Some(next)
+}
+
+/// 找出最少的蛇头插入数 k，使得 new = 新蛇头 + old 去掉若干蛇尾；
+/// k 取整条新蛇身时必然成立，相当于整体替换
+fn diff_snake(old: &Snake, new: &Snake) -> SnakeDelta {
+    let pushed = (0..=new.body.len())
+        .find(|&k| {
+            let kept = new.body.len() - k;
+            kept <= old.body.len() && new.body[k..] == old.body[..kept]
+        })
+        .unwrap_or(new.body.len());
+    let kept = new.body.len() - pushed;
+
+    SnakeDelta {
+        id: new.id,
+        // 按插入顺序排列，最后一个是新的蛇头
+        head_pushes: new.body[..pushed].iter().rev().copied().collect(),
+        tail_pops: old.body.len() - kept,
+        direction: new.direction,
+        alive: new.alive,
+        score: new.score,
+        last_input_seq: new.last_input_seq,
+        death: new.death,
+    }
+}
+
+/// `from` 中有而 `other` 中没有的食物位置
+fn positions_missing(from: &[Food], other: &[Food]) -> Vec<Position> {
+    from.iter()
+        .map(|f| f.position)
+        .filter(|p| !other.iter().any(|o| o.position == *p))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Engine, EngineConfig};
+    use crate::types::{CollisionRule, DeathCause, Direction};
+
+    fn snake(id: usize, body: &[(i32, i32)], direction: Direction) -> Snake {
+        Snake {
+            id,
+            body: body.iter().map(|&(x, y)| Position::new(x, y)).collect(),
+            direction,
+            alive: true,
+            score: 0,
+            last_input_seq: 0,
+            profile: Default::default(),
+            death: None,
+        }
+    }
+
+    // 引擎 + 摆放好蛇与食物的开局状态
+    fn setup(config: EngineConfig, snakes: Vec<Snake>, foods: &[(i32, i32)]) -> (Engine, GameState) {
+        let mut engine = Engine::new(config, 1);
+        let mut state = engine.new_game("room", &[]);
+        state.snakes = snakes;
+        state.foods = foods.iter().map(|&(x, y)| Food { position: Position::new(x, y) }).collect();
+        (engine, state)
+    }
+
+    // 增量能把 prev 还原成 next，返回该增量
+    fn round_trip(prev: &GameState, next: &GameState) -> StateDelta {
+        let delta = diff(prev, next).expect("same shape");
+        assert_eq!(apply(prev, &delta).as_ref(), Some(next));
+        delta
+    }
+
+    fn change(delta: &StateDelta, id: usize) -> &SnakeDelta {
+        delta.snakes.iter().find(|s| s.id == id).unwrap()
+    }
+
+    #[test]
+    fn move_pushes_head_and_pops_tail() {
+        let (mut engine, start) = setup(
+            EngineConfig { food_count: 0, ..EngineConfig::default() },
+            vec![snake(1, &[(5, 5), (4, 5), (3, 5)], Direction::Right)],
+            &[],
+        );
+        let next = engine.step(&start, &[(1, Direction::Down)]);
+        let delta = round_trip(&start, &next);
+        assert_eq!(change(&delta, 1).head_pushes, vec![Position::new(5, 6)]);
+        assert_eq!(change(&delta, 1).tail_pops, 1);
+        assert_eq!((delta.base_tick, delta.tick), (start.tick, next.tick));
+    }
+
+    #[test]
+    fn growth_and_food_round_trip() {
+        let (mut engine, start) = setup(
+            EngineConfig::default(),
+            vec![snake(1, &[(5, 5), (4, 5), (3, 5)], Direction::Right)],
+            &[(6, 5)],
+        );
+        let next = engine.step(&start, &[]);
+        let delta = round_trip(&start, &next);
+        assert_eq!(change(&delta, 1).head_pushes, vec![Position::new(6, 5)]);
+        assert_eq!(change(&delta, 1).tail_pops, 0);
+        assert_eq!(change(&delta, 1).score, next.snakes[0].score);
+        assert_eq!(delta.foods_eaten, vec![Position::new(6, 5)]);
+        // 吃掉的食物立即补充
+        assert_eq!(delta.foods_spawned.len(), next.foods.len());
+    }
+
+    #[test]
+    fn wrap_around_head_round_trip() {
+        let (mut engine, mut start) = setup(
+            EngineConfig { wrap: true, food_count: 0, ..EngineConfig::default() },
+            vec![snake(1, &[(0, 5), (1, 5), (2, 5)], Direction::Left)],
+            &[],
+        );
+        start.wrap = true;
+        let next = engine.step(&start, &[]);
+        let delta = round_trip(&start, &next);
+        assert_eq!(change(&delta, 1).head_pushes, vec![Position::new(start.map_width - 1, 5)]);
+        assert_eq!(change(&delta, 1).tail_pops, 1);
+    }
+
+    #[test]
+    fn bounce_reverses_the_whole_body() {
+        let (mut engine, start) = setup(
+            EngineConfig { collision_rule: CollisionRule::Bounce, food_count: 0, ..EngineConfig::default() },
+            vec![
+                snake(1, &[(5, 5), (4, 5), (3, 5), (2, 5)], Direction::Right),
+                snake(2, &[(7, 5), (8, 5), (9, 5)], Direction::Left),
+            ],
+            &[],
+        );
+        let next = engine.step(&start, &[]);
+        assert_eq!(next.snakes[0].body[0], Position::new(2, 5));
+        round_trip(&start, &next);
+    }
+
+    #[test]
+    fn deaths_and_unchanged_snakes() {
+        let mut corpse = snake(3, &[(20, 20), (20, 21)], Direction::Up);
+        corpse.alive = false;
+        let (mut engine, start) = setup(
+            EngineConfig { food_count: 0, ..EngineConfig::default() },
+            vec![
+                snake(1, &[(0, 5), (1, 5)], Direction::Left),
+                snake(2, &[(5, 10), (4, 10)], Direction::Right),
+                corpse,
+            ],
+            &[],
+        );
+        let next = engine.step(&start, &[]);
+        let delta = round_trip(&start, &next);
+        assert_eq!(change(&delta, 1).death, Some(DeathCause::Wall));
+        assert!(!change(&delta, 1).alive);
+        // 已经死亡的蛇没有变化，不出现在增量中
+        assert!(delta.snakes.iter().all(|s| s.id != 3));
+    }
+
+    #[test]
+    fn snake_set_change_has_no_delta() {
+        let (_, start) = setup(
+            EngineConfig::default(),
+            vec![snake(1, &[(5, 5)], Direction::Right), snake(2, &[(5, 8)], Direction::Right)],
+            &[],
+        );
+        let mut fewer = start.clone();
+        fewer.snakes.pop();
+        assert_eq!(diff(&start, &fewer), None);
+
+        let mut renumbered = start.clone();
+        renumbered.snakes[1].id = 3;
+        assert_eq!(diff(&start, &renumbered), None);
+
+        let mut other_room = start.clone();
+        other_room.room_id = "other".to_string();
+        assert_eq!(diff(&start, &other_room), None);
+    }
+
+    #[test]
+    fn apply_rejects_a_different_base_tick() {
+        let (mut engine, start) = setup(
+            EngineConfig { food_count: 0, ..EngineConfig::default() },
+            vec![snake(1, &[(5, 5), (4, 5)], Direction::Right)],
+            &[],
+        );
+        let next = engine.step(&start, &[]);
+        let after = engine.step(&next, &[]);
+        let delta = diff(&next, &after).unwrap();
+        // 跳过了中间一帧
+        assert_eq!(apply(&start, &delta), None);
+        // 重复收到同一帧
+        assert_eq!(apply(&after, &delta), None);
+    }
+}
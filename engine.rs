@@ -0,0 +1,1160 @@
+// engine.rs
+// 纯 Rust 的权威游戏逻辑（不依赖任何 UI），服务端、机器人与测试共用
+use std::cmp::Reverse;
+use std::collections::{HashSet, VecDeque};
+use crate::map::{MapDef, Spawn};
+use crate::types::{
+    GameState, GameEvent, CollisionRule, DeathCause, Direction, Snake, Food, Position, RoomSettings, WinCondition,
+};
+
+/// 默认地图宽度（格）
+pub const DEFAULT_MAP_WIDTH: i32 = 35;
+/// 默认地图高度（格）
+pub const DEFAULT_MAP_HEIGHT: i32 = 35;
+
+// ---------------- 引擎配置 ----------------
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineConfig {
+    pub map: MapDef,
+    /// 环形地图（房间选项）：越过边缘从对侧出现
+    pub wrap: bool,
+    pub start_length: usize,
+    pub food_count: usize,
+    pub points_per_food: u32,
+    /// 撞死其他蛇（对方撞到自己）的加分
+    pub points_per_kill: u32,
+    pub tick_interval_ms: u32,
+    pub win_condition: WinCondition,
+    pub collision_rule: CollisionRule,
+    /// 每个玩家最多排队的转向数（见 `InputQueue`）
+    pub input_queue_depth: usize,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            map: MapDef::default(),
+            wrap: false,
+            start_length: 3,
+            food_count: 3,
+            points_per_food: 10,
+            points_per_kill: 20,
+            tick_interval_ms: 150,
+            win_condition: WinCondition::LastAlive,
+            collision_rule: CollisionRule::BothDie,
+            input_queue_depth: 3,
+        }
+    }
+}
+
+impl EngineConfig {
+    /// 按房间设置生成引擎配置（设置需已通过 `settings::validate`），未涉及的项使用默认值
+    pub fn from_settings(settings: &RoomSettings) -> Self {
+        Self {
+            map: settings.layout.build(settings.map_width, settings.map_height),
+            wrap: settings.wrap,
+            start_length: settings.start_length,
+            food_count: settings.food_count,
+            tick_interval_ms: settings.tick_interval_ms,
+            win_condition: settings.win_condition,
+            collision_rule: settings.collision_rule,
+            input_queue_depth: settings.input_queue_depth,
+            ..Self::default()
+        }
+    }
+}
+
+// ---------------- 碰撞结算 ----------------
+//
+// 每个 tick 所有蛇先同时移动，再对移动后的位置统一判定，结果与蛇在状态中的顺序无关：
+//
+// 1. 迎头相撞：两条蛇的蛇头落在同一格，或相向穿过（蛇头互换位置）。相撞关系可传递，
+//    三条及以上的蛇撞在一起时作为一组结算；蛇头落在墙内或地图外的蛇不参与（按撞墙处理）。
+//    按房间的 `CollisionRule` 结算：
+//    - BothDie：组内所有蛇死亡，击杀者为组内其余蛇中最长的一条（一样长时取 ID 较小的）；
+//    - LongerWins：组内最长的蛇存活，其余死亡，击杀者为最长的蛇；最长的不止一条时按 BothDie 处理；
+//    - Bounce：组内所有蛇退回移动前的位置并掉头（见 `Snake::reverse`），本 tick 吃到的食物不算。
+//    长度按移动后计算，包含本 tick 吃到食物的增长。
+// 2. 进食：除弹回的蛇外，本 tick 吃到食物的蛇加分，食物移除。
+// 3. 撞墙（越界或障碍物）、撞自己、撞到其他蛇的身体：
+//    - 同时结算：本 tick 死亡的蛇（包括迎头相撞中落败的）仍然算障碍物，
+//      唯一例外是 LongerWins 的胜者不会撞上同组的落败者（否则蛇头重叠或互换时胜者也会死亡）；
+//    - 蛇尾让位：本 tick 离开的蛇尾格子是空的，可以进入（包括自己的蛇尾），
+//      但该蛇本 tick 吃到食物、蛇尾没有缩回时，蛇尾仍在原处；
+//    - 弹回的蛇重新占据原来的格子，本 tick 进入其中的蛇头会撞上它；
+//    - 之前死亡的蛇已从地图上移除，不算障碍物。
+// 4. 死亡：撞到其他蛇（含迎头相撞）时被撞的一方为击杀者并获得击杀加分，击杀者同一 tick 死亡也照常加分；
+//    蛇头所在格子同时属于多条蛇时（例如同一 tick 迎头相撞死亡的两条蛇头重叠），击杀者取 ID 较小的。
+// 本 tick 的进食与死亡事件各自按蛇 ID 排列。
+
+/// 本 tick 的一次死亡：(蛇ID, 死因, 击杀者)
+type Death = (usize, DeathCause, Option<usize>);
+
+/// 迎头相撞的结算结果
+#[derive(Debug, Default)]
+struct HeadOn {
+    dead: Vec<Death>,
+    // (蛇ID, 对手ID)：判定撞蛇时该蛇忽略的对手（LongerWins 中同组的落败者）
+    spared: HashSet<(usize, usize)>,
+    // 弹回的蛇
+    bounced: HashSet<usize>,
+}
+
+// ---------------- 游戏引擎 ----------------
+#[derive(Debug, Clone)]
+pub struct Engine {
+    config: EngineConfig,
+    rng: u64,
+}
+
+impl Engine {
+    /// `seed` 决定食物刷新位置，相同种子 + 相同输入得到相同结果
+    pub fn new(config: EngineConfig, seed: u64) -> Self {
+        Self {
+            config,
+            // xorshift 的状态不能为 0
+            rng: seed.max(1),
+        }
+    }
+
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    /// 创建一局新游戏：玩家放在地图的出生点上；
+    /// 地图没有出生点时均匀分布在左侧不同的行，初始向右
+    pub fn new_game(&mut self, room_id: &str, player_ids: &[usize]) -> GameState {
+        let map = &self.config.map;
+        let rows = player_ids.len() as i32 + 1;
+        let snakes = player_ids
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| {
+                let spawn = if map.spawns.is_empty() {
+                    Spawn {
+                        head: Position::new(
+                            self.config.start_length as i32 - 1,
+                            (i as i32 + 1) * map.height / rows,
+                        ),
+                        direction: Direction::Right,
+                    }
+                } else {
+                    map.spawns[i % map.spawns.len()]
+                };
+                let mut body = vec![spawn.head];
+                while body.len() < self.config.start_length {
+                    let tail = body[body.len() - 1];
+                    body.push(tail.moved(spawn.direction.opposite()));
+                }
+                Snake {
+                    id,
+                    body,
+                    direction: spawn.direction,
+                    alive: true,
+                    score: 0,
+                    last_input_seq: 0,
+                    profile: Default::default(),
+                    death: None,
+                }
+            })
+            .collect();
+
+        let mut state = GameState {
+            room_id: room_id.to_string(),
+            snakes,
+            foods: vec![],
+            game_started: true,
+            game_over: false,
+            tick: 0,
+            server_time_ms: 0,
+            tick_interval_ms: self.config.tick_interval_ms,
+            map_width: map.width,
+            map_height: map.height,
+            walls: map.wall_list(),
+            wrap: self.config.wrap,
+            events: vec![],
+            input_queue_depth: self.config.input_queue_depth,
+        };
+        self.replenish_food(&mut state);
+        state
+    }
+
+    /// 推进一个 tick，返回新的游戏状态
+    ///
+    /// `inputs` 为本 tick 各蛇从 `InputQueue` 取出的 `(蛇ID, 方向)`；
+    /// 每个 tick 每条蛇最多转向一次（同一条蛇有多个输入时只取第一个），
+    /// 与上一 tick 的移动方向相反的转向被忽略。
+    ///
+    /// 结算顺序：转向 → 移动（吃到食物则增长）→ 迎头相撞 → 进食加分 → 死亡判定（击杀者加分）
+    /// → 补充食物 → 结束判定，碰撞规则见上方"碰撞结算"。
+    /// 本 tick 的进食与死亡记录在返回状态的 `events` 中（先进食后死亡，各自按蛇 ID 排列）。
+    pub fn step(&mut self, state: &GameState, inputs: &[(usize, Direction)]) -> GameState {
+        let mut next = state.clone();
+        if !next.game_started || next.game_over {
+            return next;
+        }
+        next.tick += 1;
+        next.events.clear();
+
+        // 1. 转向
+        for snake in next.snakes.iter_mut().filter(|s| s.alive) {
+            let queued: Vec<Direction> = inputs
+                .iter()
+                .filter(|(id, _)| *id == snake.id)
+                .map(|(_, dir)| *dir)
+                .collect();
+            if let Some(dir) = select_turn(snake, &queued) {
+                snake.turn(dir);
+            }
+        }
+
+        // 2. 移动（吃到食物的蛇增长，加分在迎头相撞结算之后）
+        let mut ate: Vec<(usize, Position)> = Vec::new();
+        let (width, height, wrap) = (next.map_width, next.map_height, next.wrap);
+        for snake in next.snakes.iter_mut().filter(|s| s.alive) {
+            let Some(head) = next_head(snake, width, height, wrap) else {
+                continue;
+            };
+            let grow = next.foods.iter().any(|f| f.position == head);
+            snake.advance_to(head, grow);
+            if grow {
+                ate.push((snake.id, head));
+            }
+        }
+
+        // 3. 迎头相撞
+        let head_on = self.resolve_head_on(&state.snakes, &mut next.snakes);
+
+        // 4. 进食（弹回的蛇不算吃到）
+        ate.retain(|(id, _)| !head_on.bounced.contains(id));
+        ate.sort_by_key(|&(id, _)| id);
+        for &(id, position) in &ate {
+            if let Some(snake) = next.snakes.iter_mut().find(|s| s.id == id) {
+                snake.score += self.config.points_per_food;
+            }
+            next.events.push(GameEvent::AteFood { id, position });
+        }
+        next.foods.retain(|f| !ate.iter().any(|(_, position)| *position == f.position));
+
+        // 5. 死亡判定（基于迎头相撞结算后的位置，同时结算）
+        let mut dead = head_on.dead;
+        let decided: HashSet<usize> = dead.iter().map(|&(id, _, _)| id).collect();
+        dead.extend(
+            next.snakes
+                .iter()
+                .filter(|s| s.alive && !decided.contains(&s.id))
+                .filter_map(|s| {
+                    let (cause, killer) = self.death_cause(s, &next.snakes, &head_on.spared)?;
+                    Some((s.id, cause, killer))
+                }),
+        );
+        dead.sort_by_key(|&(id, _, _)| id);
+        for &(id, cause, killer) in &dead {
+            if let Some(snake) = next.snakes.iter_mut().find(|s| s.id == id) {
+                snake.alive = false;
+                snake.death = Some(cause);
+            }
+            // 击杀者即使在同一 tick 死亡也照常加分
+            if let Some(killer) = killer.and_then(|k| next.snakes.iter_mut().find(|s| s.id == k)) {
+                killer.score += self.config.points_per_kill;
+            }
+            next.events.push(GameEvent::Died { id, cause, killer });
+        }
+
+        // 6. 补充食物
+        self.replenish_food(&mut next);
+
+        // 7. 结束判定
+        next.game_over = self.is_over(&next);
+        next
+    }
+
+    /// 多人局剩余不超过 1 条蛇、单人局蛇已死亡，或满足胜利条件（达到目标分数 / 时间到）
+    fn is_over(&self, state: &GameState) -> bool {
+        let alive = state.snakes.iter().filter(|s| s.alive).count();
+        let eliminated = if state.snakes.len() > 1 { alive <= 1 } else { alive == 0 };
+        eliminated
+            || match self.config.win_condition {
+                WinCondition::LastAlive => false,
+                WinCondition::TargetScore(target) => state.snakes.iter().any(|s| s.score >= target),
+                WinCondition::TimeLimit(secs) => {
+                    state.tick * self.config.tick_interval_ms as u64 >= secs as u64 * 1000
+                }
+            }
+    }
+
+    /// 按 `CollisionRule` 结算迎头相撞；弹回的蛇在 `next` 中直接退回原位并掉头
+    fn resolve_head_on(&self, prev: &[Snake], next: &mut [Snake]) -> HeadOn {
+        let mut outcome = HeadOn::default();
+        for group in self.head_on_groups(prev, next) {
+            let len = |id: usize| next.iter().find(|s| s.id == id).map_or(0, |s| s.body.len());
+            // 组内其余蛇中最长的一条，一样长时取 ID 较小的（组内按 ID 排列）
+            let longest_other = |id: usize| {
+                group.iter().copied().filter(|&other| other != id).min_by_key(|&other| Reverse(len(other)))
+            };
+            match self.config.collision_rule {
+                CollisionRule::BothDie => {
+                    for &id in &group {
+                        outcome.dead.push((id, DeathCause::OtherSnake, longest_other(id)));
+                    }
+                }
+                CollisionRule::LongerWins => {
+                    let longest = group.iter().map(|&id| len(id)).max().unwrap_or_default();
+                    let mut winners = group.iter().copied().filter(|&id| len(id) == longest);
+                    let winner = match (winners.next(), winners.next()) {
+                        (Some(winner), None) => Some(winner),
+                        _ => None,
+                    };
+                    for &id in group.iter().filter(|&&id| Some(id) != winner) {
+                        outcome.dead.push((id, DeathCause::OtherSnake, winner.or_else(|| longest_other(id))));
+                        if let Some(winner) = winner {
+                            outcome.spared.insert((winner, id));
+                        }
+                    }
+                }
+                CollisionRule::Bounce => {
+                    for &id in &group {
+                        let before = prev.iter().find(|s| s.id == id);
+                        if let (Some(snake), Some(before)) = (next.iter_mut().find(|s| s.id == id), before) {
+                            *snake = before.clone();
+                            snake.reverse(self.config.map.width, self.config.map.height);
+                        }
+                        outcome.bounced.insert(id);
+                    }
+                }
+            }
+        }
+        outcome
+    }
+
+    /// 找出迎头相撞的各组蛇ID：移动后蛇头落在同一格，或两条蛇的蛇头互换了位置；
+    /// 相撞关系可传递，组内按蛇 ID 排列。蛇头落在墙内或地图外的蛇不参与
+    fn head_on_groups(&self, prev: &[Snake], next: &[Snake]) -> Vec<Vec<usize>> {
+        // 参与判定的蛇：(ID, 移动前的蛇头, 移动后的蛇头)
+        let heads: Vec<(usize, Position, Position)> = next
+            .iter()
+            .filter(|s| s.alive)
+            .filter_map(|s| {
+                let before = *prev.iter().find(|p| p.id == s.id)?.head()?;
+                let after = *s.head()?;
+                Some((s.id, before, after))
+            })
+            .filter(|(_, _, after)| !self.config.map.is_blocked(after))
+            .collect();
+
+        // 简单的并查集：相撞的两条蛇合并到同一组
+        let mut group: Vec<usize> = (0..heads.len()).collect();
+        for i in 0..heads.len() {
+            for j in i + 1..heads.len() {
+                let ((_, a_before, a_after), (_, b_before, b_after)) = (heads[i], heads[j]);
+                let collide = a_after == b_after || (a_after == b_before && b_after == a_before);
+                if collide && group[i] != group[j] {
+                    let (from, to) = (group[j], group[i]);
+                    for g in group.iter_mut().filter(|g| **g == from) {
+                        *g = to;
+                    }
+                }
+            }
+        }
+        (0..heads.len())
+            .filter(|&i| group[i] == i)
+            .map(|i| {
+                let mut members: Vec<usize> =
+                    (0..heads.len()).filter(|&j| group[j] == i).map(|j| heads[j].0).collect();
+                members.sort_unstable();
+                members
+            })
+            .filter(|members| members.len() > 1)
+            .collect()
+    }
+
+    /// 死因与击杀者：越界或撞到障碍物、撞自己、撞到其他存活的蛇（击杀者为对方，`spared` 中的对手除外，
+    /// 同时撞到多条时取 ID 最小的）；没有撞到任何东西时为 None
+    fn death_cause(
+        &self,
+        snake: &Snake,
+        snakes: &[Snake],
+        spared: &HashSet<(usize, usize)>,
+    ) -> Option<(DeathCause, Option<usize>)> {
+        let blocked = snake
+            .head()
+            .is_some_and(|head| self.config.map.is_blocked(head));
+        if blocked {
+            return Some((DeathCause::Wall, None));
+        }
+        if snake.hits_self() {
+            return Some((DeathCause::OwnBody, None));
+        }
+        snakes
+            .iter()
+            .filter(|o| o.alive && !spared.contains(&(snake.id, o.id)))
+            .filter(|o| snake.hits_other(o))
+            .min_by_key(|o| o.id)
+            .map(|o| (DeathCause::OtherSnake, Some(o.id)))
+    }
+
+    /// 在空闲格子（不含障碍物）上随机刷新食物，直到数量达到配置值
+    fn replenish_food(&mut self, state: &mut GameState) {
+        let (width, height) = (self.config.map.width, self.config.map.height);
+        let mut occupied: HashSet<Position> = state
+            .snakes
+            .iter()
+            .filter(|s| s.alive)
+            .flat_map(|s| s.body.iter().copied())
+            .chain(state.foods.iter().map(|f| f.position))
+            .chain(self.config.map.walls.iter().copied())
+            .collect();
+        let cells = (width * height) as usize;
+
+        while state.foods.len() < self.config.food_count && occupied.len() < cells {
+            let position = Position::new(
+                self.next_rand(width as u64) as i32,
+                self.next_rand(height as u64) as i32,
+            );
+            if occupied.insert(position) {
+                state.foods.push(Food { position });
+            }
+        }
+    }
+
+    /// xorshift64*，避免引擎依赖外部随机数库（需在 wasm 中复用）
+    fn next_rand(&mut self, bound: u64) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D) % bound
+    }
+}
+
+/// 按当前方向计算下一步的蛇头位置；环形地图上越过边缘的蛇头折回对侧
+///
+/// 服务端引擎与客户端预测共用，保证两边对边界的处理一致。
+pub fn next_head(snake: &Snake, width: i32, height: i32, wrap: bool) -> Option<Position> {
+    let head = snake.next_head()?;
+    Some(if wrap { head.wrapped(width, height) } else { head })
+}
+
+/// 从排队的输入中选出本 tick 生效的转向：每个 tick 最多转向一次，取队首；
+/// 队首与当前移动方向相反时（例如迎头相撞弹回后方向已改变）本 tick 不转向
+///
+/// 客户端预测（`prediction.rs`）使用同一规则，保证预测与服务端一致。
+pub fn select_turn(snake: &Snake, queued: &[Direction]) -> Option<Direction> {
+    queued
+        .first()
+        .copied()
+        .filter(|dir| snake.body.len() <= 1 || *dir != snake.direction.opposite())
+}
+
+/// 输入队列的入队规则（服务端与客户端共用）：队列已满（`depth`）时丢弃；
+/// 与"有效方向"（最后一个排队的转向，队列为空时为蛇当前的移动方向）相同或相反的转向也丢弃，
+/// 因此快速连按"上、左"可以在两个 tick 内完成掉头，而不会直接反向撞上自己
+pub fn accepts_turn(snake: &Snake, queued: &[Direction], direction: Direction, depth: usize) -> bool {
+    if queued.len() >= depth {
+        return false;
+    }
+    let effective = queued.last().copied().unwrap_or(snake.direction);
+    direction != effective && (snake.body.len() <= 1 || direction != effective.opposite())
+}
+
+/// 服务端每个玩家的输入队列：按到达顺序缓存转向，每个 tick 取出一个
+///
+/// 输入带客户端序号（旧协议的输入没有序号），转向生效或被丢弃后才记为已处理，
+/// 服务端在 `Snake.last_input_seq` 中回传，客户端预测据此保留仍在排队的输入。
+#[derive(Debug, Clone, Default)]
+pub struct InputQueue {
+    queued: VecDeque<(Option<u32>, Direction)>,
+    // 已处理（生效或被丢弃）的最大序号
+    processed: u32,
+}
+
+impl InputQueue {
+    /// 按 `accepts_turn` 入队，返回是否接受；
+    /// 被丢弃的输入排在仍在排队的输入之后，随队尾一起确认
+    pub fn push(&mut self, snake: &Snake, direction: Direction, seq: Option<u32>, depth: usize) -> bool {
+        let queued: Vec<Direction> = self.queued.iter().map(|(_, dir)| *dir).collect();
+        if accepts_turn(snake, &queued, direction, depth) {
+            self.queued.push_back((seq, direction));
+            return true;
+        }
+        match self.queued.back_mut() {
+            Some((tail_seq, _)) => *tail_seq = (*tail_seq).max(seq),
+            None => self.processed = self.processed.max(seq.unwrap_or_default()),
+        }
+        false
+    }
+
+    /// 取出本 tick 生效的转向
+    pub fn pop(&mut self) -> Option<Direction> {
+        let (seq, direction) = self.queued.pop_front()?;
+        self.processed = self.processed.max(seq.unwrap_or_default());
+        Some(direction)
+    }
+
+    pub fn processed(&self) -> u32 {
+        self.processed
+    }
+}
+
+/// 按分数从高到低排名，同分时存活者优先，再按蛇ID
+pub fn rankings(state: &GameState) -> Vec<(usize, u32)> {
+    let mut snakes: Vec<&Snake> = state.snakes.iter().collect();
+    snakes.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(b.alive.cmp(&a.alive))
+            .then(a.id.cmp(&b.id))
+    });
+    snakes.iter().map(|s| (s.id, s.score)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Direction::{Down, Left, Right, Up};
+
+    // 不自动刷新食物的引擎，便于精确摆放
+    fn test_engine(config: EngineConfig) -> Engine {
+        Engine::new(EngineConfig { food_count: 0, ..config }, 1)
+    }
+
+    fn snake(id: usize, body: &[(i32, i32)], direction: Direction) -> Snake {
+        Snake {
+            id,
+            body: body.iter().map(|&(x, y)| Position::new(x, y)).collect(),
+            direction,
+            alive: true,
+            score: 0,
+            last_input_seq: 0,
+            profile: Default::default(),
+            death: None,
+        }
+    }
+
+    fn state(engine: &mut Engine, snakes: Vec<Snake>, foods: &[(i32, i32)]) -> GameState {
+        let mut state = engine.new_game("test", &[]);
+        state.snakes = snakes;
+        state.foods = foods.iter().map(|&(x, y)| Food { position: Position::new(x, y) }).collect();
+        state
+    }
+
+    fn get(state: &GameState, id: usize) -> &Snake {
+        state.snakes.iter().find(|s| s.id == id).unwrap()
+    }
+
+    fn body(state: &GameState, id: usize) -> Vec<(i32, i32)> {
+        get(state, id).body.iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    #[test]
+    fn new_game_spreads_snakes_and_spawns_food() {
+        let mut engine = Engine::new(EngineConfig::default(), 7);
+        let state = engine.new_game("room", &[1, 2, 3]);
+        assert_eq!(state.snakes.len(), 3);
+        assert_eq!(state.foods.len(), EngineConfig::default().food_count);
+        let rows: HashSet<i32> = state.snakes.iter().map(|s| s.body[0].y).collect();
+        assert_eq!(rows.len(), 3);
+        for s in &state.snakes {
+            assert_eq!(s.body.len(), 3);
+            assert_eq!(s.direction, Right);
+            assert!(s.alive);
+            // 蛇身向左展开，蛇头在最右侧
+            assert_eq!(s.body[0].x, 2);
+            assert_eq!(s.body[2].x, 0);
+        }
+        for food in &state.foods {
+            assert!(!state.snakes.iter().any(|s| s.body.contains(&food.position)));
+        }
+    }
+
+    #[test]
+    fn snake_moves_one_cell_per_tick() {
+        let mut engine = test_engine(EngineConfig::default());
+        let start = state(&mut engine, vec![snake(1, &[(5, 5), (4, 5), (3, 5)], Right)], &[]);
+        let next = engine.step(&start, &[]);
+        assert_eq!(next.tick, 1);
+        assert_eq!(body(&next, 1), vec![(6, 5), (5, 5), (4, 5)]);
+    }
+
+    #[test]
+    fn turn_applies_but_reversal_is_ignored() {
+        let mut engine = test_engine(EngineConfig::default());
+        let start = state(&mut engine, vec![snake(1, &[(5, 5), (4, 5), (3, 5)], Right)], &[]);
+        let turned = engine.step(&start, &[(1, Up)]);
+        assert_eq!(body(&turned, 1)[0], (5, 4));
+        let reversed = engine.step(&start, &[(1, Left)]);
+        assert_eq!(body(&reversed, 1)[0], (6, 5));
+        assert!(get(&reversed, 1).alive);
+    }
+
+    #[test]
+    fn eating_food_grows_and_scores() {
+        let mut engine = test_engine(EngineConfig::default());
+        let start = state(&mut engine, vec![snake(1, &[(5, 5), (4, 5), (3, 5)], Right)], &[(6, 5)]);
+        let next = engine.step(&start, &[]);
+        assert_eq!(body(&next, 1), vec![(6, 5), (5, 5), (4, 5), (3, 5)]);
+        assert_eq!(get(&next, 1).score, engine.config().points_per_food);
+        assert!(next.foods.is_empty());
+        assert_eq!(next.events, vec![GameEvent::AteFood { id: 1, position: Position::new(6, 5) }]);
+    }
+
+    #[test]
+    fn leaving_the_map_is_a_wall_death() {
+        let mut engine = test_engine(EngineConfig::default());
+        let width = engine.config().map.width;
+        let start = state(&mut engine, vec![snake(1, &[(width - 1, 5), (width - 2, 5)], Right)], &[]);
+        let next = engine.step(&start, &[]);
+        assert!(!get(&next, 1).alive);
+        assert_eq!(get(&next, 1).death, Some(DeathCause::Wall));
+    }
+
+    #[test]
+    fn obstacle_is_a_wall_death() {
+        let mut config = EngineConfig::default();
+        config.map.walls.insert(Position::new(6, 5));
+        let mut engine = test_engine(config);
+        let start = state(&mut engine, vec![snake(1, &[(5, 5), (4, 5)], Right)], &[]);
+        let next = engine.step(&start, &[]);
+        assert_eq!(get(&next, 1).death, Some(DeathCause::Wall));
+    }
+
+    #[test]
+    fn wrap_map_crosses_the_edge() {
+        let mut engine = test_engine(EngineConfig { wrap: true, ..EngineConfig::default() });
+        let width = engine.config().map.width;
+        let mut start = state(&mut engine, vec![snake(1, &[(width - 1, 5), (width - 2, 5)], Right)], &[]);
+        start.wrap = true;
+        let next = engine.step(&start, &[]);
+        assert!(get(&next, 1).alive);
+        assert_eq!(body(&next, 1)[0], (0, 5));
+    }
+
+    #[test]
+    fn hitting_own_body_kills() {
+        let mut engine = test_engine(EngineConfig::default());
+        // 向下移动的蛇左转撞上自己的身体
+        let start = state(&mut engine, vec![snake(1, &[(5, 5), (5, 4), (4, 4), (4, 5), (4, 6)], Down)], &[]);
+        let next = engine.step(&start, &[(1, Left)]);
+        assert_eq!(get(&next, 1).death, Some(DeathCause::OwnBody));
+        assert_eq!(next.events, vec![GameEvent::Died { id: 1, cause: DeathCause::OwnBody, killer: None }]);
+    }
+
+    #[test]
+    fn hitting_other_snake_kills_and_credits_the_killer() {
+        let mut engine = test_engine(EngineConfig::default());
+        let start = state(
+            &mut engine,
+            vec![snake(1, &[(5, 5), (4, 5)], Right), snake(2, &[(6, 4), (6, 5), (6, 6), (6, 7)], Up)],
+            &[],
+        );
+        let next = engine.step(&start, &[]);
+        assert_eq!(get(&next, 1).death, Some(DeathCause::OtherSnake));
+        assert!(get(&next, 2).alive);
+        assert_eq!(get(&next, 2).score, engine.config().points_per_kill);
+        assert_eq!(next.events, vec![GameEvent::Died { id: 1, cause: DeathCause::OtherSnake, killer: Some(2) }]);
+    }
+
+    #[test]
+    fn dead_snakes_are_not_obstacles() {
+        let mut engine = test_engine(EngineConfig::default());
+        let mut corpse = snake(2, &[(6, 4), (6, 5), (6, 6)], Up);
+        corpse.alive = false;
+        let start = state(&mut engine, vec![snake(1, &[(5, 5), (4, 5)], Right), corpse, snake(3, &[(0, 0)], Right)], &[]);
+        let next = engine.step(&start, &[]);
+        assert!(get(&next, 1).alive);
+    }
+
+    #[test]
+    fn same_seed_and_inputs_give_same_game() {
+        let play = |seed: u64| {
+            let mut engine = Engine::new(EngineConfig::default(), seed);
+            let mut state = engine.new_game("room", &[1, 2]);
+            let inputs = [vec![(1, Down)], vec![], vec![(2, Up)], vec![(1, Right)], vec![]];
+            for tick in 0..30 {
+                state = engine.step(&state, &inputs[tick % inputs.len()]);
+            }
+            state
+        };
+        assert_eq!(play(42), play(42));
+        assert_ne!(play(42).foods, play(43).foods);
+    }
+
+    #[test]
+    fn finished_game_does_not_advance() {
+        let mut engine = test_engine(EngineConfig::default());
+        let mut start = state(&mut engine, vec![snake(1, &[(5, 5)], Right)], &[]);
+        start.game_over = true;
+        assert_eq!(engine.step(&start, &[]), start);
+    }
+
+    #[test]
+    fn single_player_game_ends_when_the_snake_dies() {
+        let mut engine = test_engine(EngineConfig::default());
+        let alive = state(&mut engine, vec![snake(1, &[(5, 5)], Right)], &[]);
+        assert!(!engine.step(&alive, &[]).game_over);
+        let width = engine.config().map.width;
+        let dying = state(&mut engine, vec![snake(1, &[(width - 1, 5)], Right)], &[]);
+        assert!(engine.step(&dying, &[]).game_over);
+    }
+
+    #[test]
+    fn multiplayer_game_ends_when_one_snake_is_left() {
+        let mut engine = test_engine(EngineConfig::default());
+        let width = engine.config().map.width;
+        let start = state(
+            &mut engine,
+            vec![
+                snake(1, &[(width - 1, 1)], Right),
+                snake(2, &[(5, 10)], Right),
+                snake(3, &[(5, 20)], Right),
+            ],
+            &[],
+        );
+        let next = engine.step(&start, &[]);
+        assert!(!get(&next, 1).alive);
+        assert!(!next.game_over);
+
+        let last = state(&mut engine, vec![snake(1, &[(width - 1, 1)], Right), snake(2, &[(5, 10)], Right)], &[]);
+        assert!(engine.step(&last, &[]).game_over);
+    }
+
+    #[test]
+    fn target_score_and_time_limit_end_the_game() {
+        let mut engine = test_engine(EngineConfig { win_condition: WinCondition::TargetScore(10), ..EngineConfig::default() });
+        let start = state(&mut engine, vec![snake(1, &[(5, 5)], Right), snake(2, &[(5, 10)], Right)], &[(6, 5)]);
+        assert!(engine.step(&start, &[]).game_over);
+
+        let mut engine = test_engine(EngineConfig {
+            win_condition: WinCondition::TimeLimit(1),
+            tick_interval_ms: 500,
+            ..EngineConfig::default()
+        });
+        let start = state(&mut engine, vec![snake(1, &[(5, 5)], Right), snake(2, &[(5, 10)], Right)], &[]);
+        let first = engine.step(&start, &[]);
+        assert!(!first.game_over);
+        assert!(engine.step(&first, &[]).game_over);
+    }
+
+    #[test]
+    fn rankings_order_by_score_then_alive_then_id() {
+        let mut engine = test_engine(EngineConfig::default());
+        let mut snakes = vec![
+            snake(4, &[(1, 1)], Right),
+            snake(3, &[(1, 2)], Right),
+            snake(2, &[(1, 3)], Right),
+            snake(1, &[(1, 4)], Right),
+        ];
+        snakes[0].score = 10;
+        snakes[1].score = 20;
+        snakes[2].score = 10;
+        snakes[2].alive = false;
+        snakes[3].score = 10;
+        let state = state(&mut engine, snakes, &[]);
+        assert_eq!(rankings(&state), vec![(3, 20), (1, 10), (4, 10), (2, 10)]);
+    }
+
+    // ---------------- 碰撞结算 ----------------
+
+    fn rule_engine(collision_rule: CollisionRule) -> Engine {
+        test_engine(EngineConfig { collision_rule, ..EngineConfig::default() })
+    }
+
+    fn died(id: usize, cause: DeathCause, killer: Option<usize>) -> GameEvent {
+        GameEvent::Died { id, cause, killer }
+    }
+
+    // 两条蛇的蛇头同时进入 (6, 5)
+    fn same_cell(len_a: i32, len_b: i32) -> Vec<Snake> {
+        let a: Vec<(i32, i32)> = (0..len_a).map(|i| (5 - i, 5)).collect();
+        let b: Vec<(i32, i32)> = (0..len_b).map(|i| (7 + i, 5)).collect();
+        vec![snake(1, &a, Right), snake(2, &b, Left)]
+    }
+
+    // 两条蛇相向而行，蛇头互换位置
+    fn head_swap(len_a: i32, len_b: i32) -> Vec<Snake> {
+        let a: Vec<(i32, i32)> = (0..len_a).map(|i| (5 - i, 5)).collect();
+        let b: Vec<(i32, i32)> = (0..len_b).map(|i| (6 + i, 5)).collect();
+        vec![snake(1, &a, Right), snake(2, &b, Left)]
+    }
+
+    #[test]
+    fn both_die_same_cell() {
+        let mut engine = rule_engine(CollisionRule::BothDie);
+        let start = state(&mut engine, same_cell(3, 4), &[]);
+        let next = engine.step(&start, &[]);
+        assert_eq!(
+            next.events,
+            vec![died(1, DeathCause::OtherSnake, Some(2)), died(2, DeathCause::OtherSnake, Some(1))]
+        );
+        assert!(!get(&next, 1).alive && !get(&next, 2).alive);
+    }
+
+    #[test]
+    fn both_die_head_swap() {
+        let mut engine = rule_engine(CollisionRule::BothDie);
+        let start = state(&mut engine, head_swap(3, 3), &[]);
+        let next = engine.step(&start, &[]);
+        assert_eq!(
+            next.events,
+            vec![died(1, DeathCause::OtherSnake, Some(2)), died(2, DeathCause::OtherSnake, Some(1))]
+        );
+    }
+
+    #[test]
+    fn killer_scores_even_when_it_dies_the_same_tick() {
+        let mut engine = rule_engine(CollisionRule::BothDie);
+        let start = state(&mut engine, same_cell(3, 3), &[]);
+        let next = engine.step(&start, &[]);
+        let points = engine.config().points_per_kill;
+        assert_eq!((get(&next, 1).score, get(&next, 2).score), (points, points));
+
+        // 2 撞墙死亡的同时，它的身体仍挡住了 1
+        let column: Vec<(i32, i32)> = (0..7).map(|y| (6, y)).collect();
+        let start = state(&mut engine, vec![snake(1, &[(5, 5), (4, 5)], Right), snake(2, &column, Up)], &[]);
+        let next = engine.step(&start, &[]);
+        assert_eq!(
+            next.events,
+            vec![died(1, DeathCause::OtherSnake, Some(2)), died(2, DeathCause::Wall, None)]
+        );
+        assert_eq!(get(&next, 2).score, points);
+    }
+
+    #[test]
+    fn longer_wins_same_cell() {
+        let mut engine = rule_engine(CollisionRule::LongerWins);
+        let start = state(&mut engine, same_cell(4, 3), &[]);
+        let next = engine.step(&start, &[]);
+        assert_eq!(next.events, vec![died(2, DeathCause::OtherSnake, Some(1))]);
+        // 胜者的蛇头与落败者的蛇头重叠，但不会撞上它
+        assert!(get(&next, 1).alive);
+        assert_eq!(body(&next, 1)[0], (6, 5));
+        assert_eq!(get(&next, 1).score, engine.config().points_per_kill);
+    }
+
+    #[test]
+    fn longer_wins_head_swap() {
+        let mut engine = rule_engine(CollisionRule::LongerWins);
+        let start = state(&mut engine, head_swap(2, 3), &[]);
+        let next = engine.step(&start, &[]);
+        assert_eq!(next.events, vec![died(1, DeathCause::OtherSnake, Some(2))]);
+        assert!(get(&next, 2).alive);
+    }
+
+    #[test]
+    fn longer_wins_tie_is_both_die() {
+        let mut engine = rule_engine(CollisionRule::LongerWins);
+        let start = state(&mut engine, same_cell(3, 3), &[]);
+        let next = engine.step(&start, &[]);
+        assert_eq!(
+            next.events,
+            vec![died(1, DeathCause::OtherSnake, Some(2)), died(2, DeathCause::OtherSnake, Some(1))]
+        );
+    }
+
+    // 三条蛇的蛇头同时进入 (6, 5)
+    fn three_way(len_a: i32, len_b: i32, len_c: i32) -> Vec<Snake> {
+        let mut snakes = same_cell(len_a, len_b);
+        let c: Vec<(i32, i32)> = (0..len_c).map(|i| (6, 6 + i)).collect();
+        snakes.push(snake(3, &c, Up));
+        snakes
+    }
+
+    #[test]
+    fn longer_wins_three_snake_group() {
+        let mut engine = rule_engine(CollisionRule::LongerWins);
+        let start = state(&mut engine, three_way(3, 4, 2), &[]);
+        let next = engine.step(&start, &[]);
+        assert_eq!(
+            next.events,
+            vec![died(1, DeathCause::OtherSnake, Some(2)), died(3, DeathCause::OtherSnake, Some(2))]
+        );
+        assert!(get(&next, 2).alive);
+        assert_eq!(get(&next, 2).score, 2 * engine.config().points_per_kill);
+    }
+
+    #[test]
+    fn longer_wins_tied_three_snake_group_all_die() {
+        let mut engine = rule_engine(CollisionRule::LongerWins);
+        let start = state(&mut engine, three_way(3, 3, 2), &[]);
+        let next = engine.step(&start, &[]);
+        // 最长的不止一条：全部死亡，3 的击杀者在一样长的 1、2 中取 ID 较小的
+        assert_eq!(
+            next.events,
+            vec![
+                died(1, DeathCause::OtherSnake, Some(2)),
+                died(2, DeathCause::OtherSnake, Some(1)),
+                died(3, DeathCause::OtherSnake, Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn both_die_three_snake_group() {
+        let mut engine = rule_engine(CollisionRule::BothDie);
+        let start = state(&mut engine, three_way(2, 3, 4), &[]);
+        let next = engine.step(&start, &[]);
+        // 击杀者为其余蛇中最长的一条
+        assert_eq!(
+            next.events,
+            vec![
+                died(1, DeathCause::OtherSnake, Some(3)),
+                died(2, DeathCause::OtherSnake, Some(3)),
+                died(3, DeathCause::OtherSnake, Some(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn bounce_same_cell_reverses_and_skips_food() {
+        let mut engine = rule_engine(CollisionRule::Bounce);
+        let start = state(&mut engine, same_cell(3, 3), &[(6, 5)]);
+        let next = engine.step(&start, &[]);
+        assert!(next.events.is_empty());
+        assert_eq!(next.foods.len(), 1);
+        assert_eq!(body(&next, 1), vec![(3, 5), (4, 5), (5, 5)]);
+        assert_eq!(get(&next, 1).direction, Left);
+        assert_eq!(body(&next, 2), vec![(9, 5), (8, 5), (7, 5)]);
+        assert_eq!(get(&next, 2).direction, Right);
+        assert_eq!((get(&next, 1).score, get(&next, 2).score), (0, 0));
+    }
+
+    #[test]
+    fn bounce_head_swap() {
+        let mut engine = rule_engine(CollisionRule::Bounce);
+        let start = state(&mut engine, head_swap(2, 3), &[]);
+        let next = engine.step(&start, &[]);
+        assert!(next.events.is_empty());
+        assert_eq!(body(&next, 1), vec![(4, 5), (5, 5)]);
+        assert_eq!(body(&next, 2), vec![(8, 5), (7, 5), (6, 5)]);
+        // 下一 tick 沿新方向离开，不再相撞
+        let after = engine.step(&next, &[]);
+        assert_eq!(body(&after, 1)[0], (3, 5));
+        assert_eq!(body(&after, 2)[0], (9, 5));
+    }
+
+    #[test]
+    fn head_may_enter_a_tail_that_moves_away() {
+        let mut engine = test_engine(EngineConfig::default());
+        let start = state(&mut engine, vec![snake(1, &[(5, 5), (4, 5)], Right), snake(2, &[(6, 4), (6, 5)], Up)], &[]);
+        let next = engine.step(&start, &[]);
+        assert!(next.events.is_empty());
+        assert_eq!(body(&next, 1)[0], (6, 5));
+
+        // 自己的蛇尾同样让位
+        let start = state(&mut engine, vec![snake(1, &[(5, 5), (5, 6), (4, 6), (4, 5)], Left)], &[]);
+        let next = engine.step(&start, &[]);
+        assert!(get(&next, 1).alive);
+    }
+
+    #[test]
+    fn tail_stays_when_its_owner_just_ate() {
+        let mut engine = test_engine(EngineConfig::default());
+        let start = state(
+            &mut engine,
+            vec![snake(1, &[(5, 5), (4, 5)], Right), snake(2, &[(6, 4), (6, 5)], Up)],
+            &[(6, 3)],
+        );
+        let next = engine.step(&start, &[]);
+        assert_eq!(
+            next.events,
+            vec![
+                GameEvent::AteFood { id: 2, position: Position::new(6, 3) },
+                died(1, DeathCause::OtherSnake, Some(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn head_entering_a_bounced_snake_dies() {
+        let mut engine = rule_engine(CollisionRule::Bounce);
+        // 3 进入 1 的蛇尾格子 (3, 5)：1 正常移动时会让出，弹回后重新占据
+        let mut snakes = same_cell(3, 3);
+        snakes.push(snake(3, &[(3, 4), (3, 3)], Down));
+        let start = state(&mut engine, snakes, &[]);
+        let next = engine.step(&start, &[]);
+        // 弹回后 1 的蛇头正是原来的蛇尾，与 3 的蛇头重叠，双方都死亡
+        assert_eq!(
+            next.events,
+            vec![died(1, DeathCause::OtherSnake, Some(3)), died(3, DeathCause::OtherSnake, Some(1))]
+        );
+        assert!(get(&next, 2).alive);
+    }
+
+    #[test]
+    fn head_on_into_a_wall_is_a_wall_death() {
+        for rule in CollisionRule::ALL {
+            let mut config = EngineConfig { collision_rule: rule, ..EngineConfig::default() };
+            config.map.walls.insert(Position::new(6, 5));
+            let mut engine = test_engine(config);
+            let start = state(&mut engine, same_cell(3, 4), &[]);
+            let next = engine.step(&start, &[]);
+            assert_eq!(
+                next.events,
+                vec![died(1, DeathCause::Wall, None), died(2, DeathCause::Wall, None)],
+                "{:?}",
+                rule
+            );
+        }
+    }
+
+    #[test]
+    fn head_swap_across_the_wrap_edge() {
+        let mut engine = test_engine(EngineConfig { wrap: true, ..EngineConfig::default() });
+        let width = engine.config().map.width;
+        let snakes = vec![snake(1, &[(0, 5), (1, 5)], Left), snake(2, &[(width - 1, 5), (width - 2, 5)], Right)];
+        let mut start = state(&mut engine, snakes, &[]);
+        start.wrap = true;
+        let next = engine.step(&start, &[]);
+        assert_eq!(
+            next.events,
+            vec![died(1, DeathCause::OtherSnake, Some(2)), died(2, DeathCause::OtherSnake, Some(1))]
+        );
+    }
+
+    #[test]
+    fn bounce_across_the_wrap_edge_keeps_heading_away() {
+        let mut engine = test_engine(EngineConfig {
+            wrap: true,
+            collision_rule: CollisionRule::Bounce,
+            ..EngineConfig::default()
+        });
+        let width = engine.config().map.width;
+        // 1 的蛇身跨越左右边缘，掉头后应沿跨越边缘的方向（向左）离开
+        let snakes = vec![snake(1, &[(0, 5), (width - 1, 5)], Right), snake(2, &[(2, 5), (3, 5)], Left)];
+        let mut start = state(&mut engine, snakes, &[]);
+        start.wrap = true;
+        let next = engine.step(&start, &[]);
+        assert!(next.events.is_empty());
+        assert_eq!(body(&next, 1), vec![(width - 1, 5), (0, 5)]);
+        assert_eq!(get(&next, 1).direction, Left);
+    }
+
+    #[test]
+    fn direction_to_only_accepts_adjacent_cells() {
+        let (w, h) = (35, 35);
+        let at = Position::new;
+        assert_eq!(at(5, 5).direction_to(&at(6, 5), w, h), Some(Right));
+        assert_eq!(at(5, 5).direction_to(&at(4, 5), w, h), Some(Left));
+        assert_eq!(at(5, 5).direction_to(&at(5, 6), w, h), Some(Down));
+        assert_eq!(at(5, 5).direction_to(&at(5, 4), w, h), Some(Up));
+        assert_eq!(at(0, 5).direction_to(&at(w - 1, 5), w, h), Some(Left));
+        assert_eq!(at(w - 1, 5).direction_to(&at(0, 5), w, h), Some(Right));
+        assert_eq!(at(5, 0).direction_to(&at(5, h - 1), w, h), Some(Up));
+        assert_eq!(at(3, 0).direction_to(&at(0, 0), w, h), None);
+        assert_eq!(at(0, 0).direction_to(&at(3, 0), w, h), None);
+        assert_eq!(at(5, 5).direction_to(&at(5, 5), w, h), None);
+        assert_eq!(at(5, 5).direction_to(&at(6, 6), w, h), None);
+    }
+
+    // 全排列，用于验证结算结果与蛇在状态中的顺序无关
+    fn permutations(items: &[Snake]) -> Vec<Vec<Snake>> {
+        if items.len() <= 1 {
+            return vec![items.to_vec()];
+        }
+        (0..items.len())
+            .flat_map(|i| {
+                let mut rest = items.to_vec();
+                let first = rest.remove(i);
+                permutations(&rest).into_iter().map(move |mut tail| {
+                    tail.insert(0, first.clone());
+                    tail
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn outcome_does_not_depend_on_snake_order() {
+        // 三条蛇迎头相撞、一条进入其中一条的蛇尾格子、一条吃到食物
+        let mut snakes = three_way(3, 3, 2);
+        snakes.push(snake(4, &[(3, 4), (3, 3)], Down));
+        snakes.push(snake(5, &[(10, 10), (9, 10)], Right));
+        for rule in CollisionRule::ALL {
+            let mut engine = rule_engine(rule);
+            let outcome = |state: &GameState| {
+                let mut snakes: Vec<_> = state
+                    .snakes
+                    .iter()
+                    .map(|s| (s.id, s.body.clone(), s.direction, s.alive, s.death, s.score))
+                    .collect();
+                snakes.sort_by_key(|s| s.0);
+                (snakes, state.events.clone(), state.foods.clone())
+            };
+            let start = state(&mut engine, snakes.clone(), &[(11, 10)]);
+            let expected = outcome(&engine.step(&start, &[]));
+            for order in permutations(&snakes) {
+                let start = state(&mut engine, order, &[(11, 10)]);
+                assert_eq!(outcome(&engine.step(&start, &[])), expected, "{:?}", rule);
+            }
+        }
+    }
+
+    // ---------------- 输入队列 ----------------
+
+    #[test]
+    fn queue_stops_accepting_at_depth() {
+        let moving = snake(1, &[(5, 5), (4, 5), (3, 5)], Right);
+        let mut queue = InputQueue::default();
+        assert!(queue.push(&moving, Up, Some(1), 2));
+        assert!(queue.push(&moving, Right, Some(2), 2));
+        assert!(!queue.push(&moving, Down, Some(3), 2));
+        assert_eq!((queue.pop(), queue.pop(), queue.pop()), (Some(Up), Some(Right), None));
+    }
+
+    #[test]
+    fn reversal_is_checked_against_the_last_queued_turn() {
+        let moving = snake(1, &[(5, 5), (4, 5), (3, 5)], Right);
+        assert!(!accepts_turn(&moving, &[], Left, 3));
+        assert!(!accepts_turn(&moving, &[], Right, 3));
+        // 排队了"上"之后，"下"是反向，"左"不是
+        assert!(!accepts_turn(&moving, &[Up], Down, 3));
+        assert!(!accepts_turn(&moving, &[Up], Up, 3));
+        assert!(accepts_turn(&moving, &[Up], Left, 3));
+        // 出队时再按蛇实际的移动方向检查一次
+        assert_eq!(select_turn(&moving, &[Left]), None);
+        assert_eq!(select_turn(&moving, &[Up, Left]), Some(Up));
+    }
+
+    #[test]
+    fn quick_up_left_makes_a_u_turn() {
+        let mut engine = test_engine(EngineConfig::default());
+        let mut state = state(&mut engine, vec![snake(1, &[(5, 5), (4, 5), (3, 5)], Right)], &[]);
+        let mut queue = InputQueue::default();
+        assert!(queue.push(get(&state, 1), Up, Some(1), 3));
+        assert!(queue.push(get(&state, 1), Left, Some(2), 3));
+        for _ in 0..2 {
+            let inputs: Vec<(usize, Direction)> = queue.pop().map(|dir| (1, dir)).into_iter().collect();
+            state = engine.step(&state, &inputs);
+        }
+        assert!(get(&state, 1).alive);
+        assert_eq!(body(&state, 1), vec![(4, 4), (5, 4), (5, 5)]);
+        assert_eq!(get(&state, 1).direction, Left);
+    }
+
+    #[test]
+    fn single_cell_snake_may_reverse() {
+        let mut engine = test_engine(EngineConfig::default());
+        let start = state(&mut engine, vec![snake(1, &[(5, 5)], Right)], &[]);
+        assert!(accepts_turn(get(&start, 1), &[], Left, 3));
+        assert_eq!(select_turn(get(&start, 1), &[Left]), Some(Left));
+        let next = engine.step(&start, &[(1, Left)]);
+        assert_eq!(body(&next, 1), vec![(4, 5)]);
+    }
+
+    #[test]
+    fn rejected_input_is_acked_with_the_queue_tail() {
+        let moving = snake(1, &[(5, 5), (4, 5), (3, 5)], Right);
+        let mut queue = InputQueue::default();
+        // 队列为空：被丢弃的输入立即确认
+        assert!(!queue.push(&moving, Left, Some(1), 3));
+        assert_eq!(queue.processed(), 1);
+        // 队列非空：随队尾一起确认
+        assert!(queue.push(&moving, Up, Some(2), 3));
+        assert!(!queue.push(&moving, Down, Some(3), 3));
+        assert_eq!(queue.processed(), 1);
+        assert_eq!(queue.pop(), Some(Up));
+        assert_eq!(queue.processed(), 3);
+        // 旧协议的输入没有序号，不影响已确认的序号
+        assert!(!queue.push(&moving, Left, None, 3));
+        assert_eq!(queue.processed(), 3);
+    }
+}
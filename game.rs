@@ -1,6 +1,22 @@
 // game.rs
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
 use yew::prelude::*;
-use crate::types::{GameState, Direction, Snake, Food};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlInputElement, HtmlSelectElement};
+use crate::types::{
+    GameState, Direction, Snake, Food, Position, RoomInfo, RoomDetails, RoomSettings, WinCondition, CollisionRule, PlayerProfile,
+    GameMode, Leaderboard, LeaderboardPeriod, PlayerStats, MatchSummary,
+};
+use crate::config::room_share_url;
+use crate::map::MapLayout;
+use crate::settings;
+use crate::profile;
+use crate::killfeed::KillFeedEntry;
+use crate::engine::{DEFAULT_MAP_WIDTH, DEFAULT_MAP_HEIGHT};
+use crate::websocket::{ConnectionState, NetStats};
 
 // ---------------- 虚拟键盘组件 ----------------
 #[function_component(VirtualKeyboard)]
@@ -57,10 +73,13 @@ pub struct VirtualKeyboardProps {
 // ---------------- 匹配状态组件（原 MatchingStatus）----------------
 #[function_component(MatchingStatus)]
 pub fn matching_status(props: &MatchingStatusProps) -> Html {
-    let MatchingStatusProps { current, required, on_ready, is_ready } = props;
+    let MatchingStatusProps { current, required, on_ready, is_ready, rating } = props;
     html! {
         <div class="matching">
             <p>{ "匹配玩家: " }{ current }{ "/" }{ required }</p>
+            if let Some((rating, games)) = rating {
+                <p class="rating">{ format!("积分 {}（{} 局）", rating, games) }</p>
+            }
             // 仅在已标记为准备时禁用按钮。允许在房间已满时仍可点击"准备"
             <button class="ready-btn" onclick={on_ready.clone()} disabled={*is_ready}>
                 { if *is_ready { "已准备" } else { "准备开始" } }
@@ -75,26 +94,733 @@ pub struct MatchingStatusProps {
     pub required: usize,
     pub on_ready: Callback<MouseEvent>,
     pub is_ready: bool,
+    // (积分, 已计分对局数)，服务端推送前为 None
+    #[prop_or_default]
+    pub rating: Option<(u32, u32)>,
+}
+
+// ---------------- 排行榜组件（总榜 / 赛季榜 / 周榜，可按玩法筛选；只统计快速匹配对局）----------------
+#[function_component(LeaderboardView)]
+pub fn leaderboard_view(props: &LeaderboardViewProps) -> Html {
+    let LeaderboardViewProps { board, on_query } = props;
+    let open = use_state(|| false);
+    let period = use_state(LeaderboardPeriod::default);
+    let mode = use_state(|| None::<GameMode>);
+    // 展开面板或切换筛选条件时重新查询
+    {
+        let on_query = on_query.clone();
+        use_effect_with((*open, *period, *mode), move |&(open, period, mode)| {
+            if open {
+                on_query.emit((period, mode));
+            }
+            || ()
+        });
+    }
+
+    let toggle = {
+        let open = open.clone();
+        Callback::from(move |_: MouseEvent| open.set(!*open))
+    };
+    let on_mode = {
+        let mode = mode.clone();
+        // 第一项为"全部玩法"
+        Callback::from(move |e: Event| {
+            let index = e.target_unchecked_into::<HtmlSelectElement>().selected_index();
+            let selected = usize::try_from(index - 1).ok().and_then(|i| GameMode::ALL.get(i));
+            mode.set(selected.copied());
+        })
+    };
+    // 只显示与当前筛选条件一致的结果，其余视为加载中
+    let current = board.as_ref().filter(|b| b.period == *period && b.mode == *mode);
+
+    html! {
+        <div class="leaderboard">
+            <button class="refresh-btn" onclick={toggle}>
+                { if *open { "收起排行榜" } else { "排行榜" } }
+            </button>
+            if *open {
+                <div class="profile-options">
+                    { for LeaderboardPeriod::ALL.iter().map(|&p| {
+                        let period = period.clone();
+                        html! {
+                            <button
+                                class={classes!("refresh-btn", (*period == p).then_some("selected"))}
+                                onclick={Callback::from(move |_: MouseEvent| period.set(p))}
+                            >{ p.name() }</button>
+                        }
+                    }) }
+                    <select onchange={on_mode}>
+                        <option selected={mode.is_none()}>{ "全部玩法" }</option>
+                        { for GameMode::ALL.iter().map(|m| html! {
+                            <option selected={*mode == Some(*m)}>{ m.name() }</option>
+                        }) }
+                    </select>
+                </div>
+                {
+                    match current {
+                        None => html! { <p class="room-empty">{ "加载中…" }</p> },
+                        Some(board) if board.entries.is_empty() => html! { <p class="room-empty">{ "暂无快速匹配对局记录" }</p> },
+                        Some(board) => html! {
+                            <table class="leaderboard-table">
+                                <tr>
+                                    <th>{ "名次" }</th><th>{ "玩家" }</th><th>{ "胜场" }</th>
+                                    <th>{ "场次" }</th><th>{ "最高分" }</th><th>{ "总分" }</th>
+                                </tr>
+                                { for board.entries.iter().map(|entry| html! {
+                                    <tr>
+                                        <td>{ entry.rank }</td>
+                                        <td>{ &entry.name }</td>
+                                        <td>{ entry.wins }</td>
+                                        <td>{ entry.games }</td>
+                                        <td>{ entry.best_score }</td>
+                                        <td>{ entry.total_score }</td>
+                                    </tr>
+                                }) }
+                            </table>
+                        },
+                    }
+                }
+            }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct LeaderboardViewProps {
+    // 最近一次查询结果
+    #[prop_or_default]
+    pub board: Option<Leaderboard>,
+    pub on_query: Callback<(LeaderboardPeriod, Option<GameMode>)>,
+}
+
+// ---------------- 个人战绩组件 ----------------
+#[function_component(StatsView)]
+pub fn stats_view(props: &StatsViewProps) -> Html {
+    let StatsViewProps { stats, on_query } = props;
+    let open = use_state(|| false);
+    // 每次展开时重新查询
+    {
+        let on_query = on_query.clone();
+        use_effect_with(*open, move |&open| {
+            if open {
+                on_query.emit(());
+            }
+            || ()
+        });
+    }
+    let toggle = {
+        let open = open.clone();
+        Callback::from(move |_: MouseEvent| open.set(!*open))
+    };
+
+    html! {
+        <div class="leaderboard">
+            <button class="refresh-btn" onclick={toggle}>
+                { if *open { "收起战绩" } else { "我的战绩" } }
+            </button>
+            if *open {
+                {
+                    match stats {
+                        None => html! { <p class="room-empty">{ "加载中…" }</p> },
+                        Some(stats) if stats.games == 0 => html! { <p class="room-empty">{ "还没有完成的对局" }</p> },
+                        Some(stats) => html! {
+                            <>
+                                <div class="stats-summary">
+                                    <span>{ format!("场次 {}", stats.games) }</span>
+                                    // 胜率只统计多人对局
+                                    <span>{
+                                        if stats.multiplayer_games == 0 {
+                                            "胜率 -".to_string()
+                                        } else {
+                                            format!("胜率 {:.0}%", stats.wins as f64 * 100.0 / stats.multiplayer_games as f64)
+                                        }
+                                    }</span>
+                                    <span>{ format!("平均分 {:.1}", stats.total_score as f64 / stats.games as f64) }</span>
+                                    <span>{ format!("最高分 {}", stats.best_score) }</span>
+                                    <span>{ format!("最长 {}", stats.longest) }</span>
+                                </div>
+                                <table class="leaderboard-table">
+                                    <tr>
+                                        <th>{ "时间" }</th><th>{ "玩法" }</th><th>{ "名次" }</th>
+                                        <th>{ "得分" }</th><th>{ "最长" }</th><th>{ "结局" }</th>
+                                    </tr>
+                                    { for stats.recent.iter().map(render_match_summary) }
+                                </table>
+                            </>
+                        },
+                    }
+                }
+            }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct StatsViewProps {
+    // 最近一次查询结果
+    #[prop_or_default]
+    pub stats: Option<PlayerStats>,
+    pub on_query: Callback<()>,
+}
+
+// 最近对局中的一行，悬停显示对局时长与全部参与者
+fn render_match_summary(summary: &MatchSummary) -> Html {
+    let secs = summary.duration_ms / 1000;
+    let mut detail = format!("时长 {}:{:02}", secs / 60, secs % 60);
+    for p in &summary.participants {
+        detail.push_str(&format!("\n{}. {} {}分", p.place + 1, p.name, p.score));
+    }
+    let guests = (summary.players as usize).saturating_sub(summary.participants.len());
+    if guests > 0 {
+        detail.push_str(&format!("\n另有 {} 名游客", guests));
+    }
+    let me = &summary.me;
+    html! {
+        <tr title={detail}>
+            <td>{ format_time(summary.ended_at_ms) }</td>
+            <td>{ summary.mode.name() }</td>
+            <td>{ format!("{}/{}", me.place + 1, summary.players) }</td>
+            <td>{ me.score }</td>
+            <td>{ me.max_length }</td>
+            <td>{ me.death.map_or("存活", |cause| cause.name()) }</td>
+        </tr>
+    }
+}
+
+// Unix 毫秒转为本地时间"月-日 时:分"
+fn format_time(ms: u64) -> String {
+    let date = js_sys::Date::new(&(ms as f64).into());
+    format!(
+        "{:02}-{:02} {:02}:{:02}",
+        date.get_month() + 1,
+        date.get_date(),
+        date.get_hours(),
+        date.get_minutes()
+    )
+}
+
+// ---------------- 账号组件（登录 / 注册 / 退出登录）----------------
+#[function_component(AccountPanel)]
+pub fn account_panel(props: &AccountPanelProps) -> Html {
+    let AccountPanelProps { account, error, on_login, on_register, on_logout } = props;
+    let username = use_state(String::new);
+    let password = use_state(String::new);
+
+    if let Some(name) = account {
+        return html! {
+            <div class="account-panel">
+                { "已登录：" }<strong>{ name }</strong>
+                <button class="leave-btn" onclick={on_logout.clone()}>{ "退出登录" }</button>
+            </div>
+        };
+    }
+
+    let on_username = {
+        let username = username.clone();
+        Callback::from(move |e: InputEvent| username.set(e.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+    let on_password = {
+        let password = password.clone();
+        Callback::from(move |e: InputEvent| password.set(e.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+    let submit = |callback: &Callback<(String, String)>| {
+        let callback = callback.clone();
+        let username = username.clone();
+        let password = password.clone();
+        Callback::from(move |_: MouseEvent| {
+            callback.emit((username.trim().to_string(), (*password).clone()));
+            password.set(String::new());
+        })
+    };
+    let incomplete = username.trim().is_empty() || password.is_empty();
+
+    html! {
+        <div class="account-panel">
+            <div class="room-form">
+                <input placeholder="用户名" value={(*username).clone()} oninput={on_username} />
+                <input type="password" placeholder="密码" value={(*password).clone()} oninput={on_password} />
+                <button class="ready-btn" onclick={submit(on_login)} disabled={incomplete}>{ "登录" }</button>
+                <button class="refresh-btn" onclick={submit(on_register)} disabled={incomplete}>{ "注册" }</button>
+            </div>
+            if let Some(error) = error {
+                <p class="room-error">{ error }</p>
+            }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct AccountPanelProps {
+    pub account: Option<String>, // 已登录的用户名
+    #[prop_or_default]
+    pub error: Option<String>,
+    pub on_login: Callback<(String, String)>, // (用户名, 密码)
+    pub on_register: Callback<(String, String)>,
+    pub on_logout: Callback<MouseEvent>,
+}
+
+// ---------------- 玩家资料组件（昵称 / 颜色 / 头像）----------------
+#[function_component(ProfileEditor)]
+pub fn profile_editor(props: &ProfileEditorProps) -> Html {
+    let ProfileEditorProps { profile: current, error, on_save } = props;
+    let draft = use_state(|| current.clone());
+    // 服务端接受的资料变化时（如连接后恢复上次保存的资料）同步草稿
+    {
+        let draft = draft.clone();
+        use_effect_with(current.clone(), move |current| {
+            draft.set(current.clone());
+            || ()
+        });
+    }
+    // 与服务端使用同一规则先在本地校验
+    let invalid = profile::validate(&profile::normalize((*draft).clone())).err();
+
+    let on_name = {
+        let draft = draft.clone();
+        Callback::from(move |e: InputEvent| {
+            let name = e.target_unchecked_into::<HtmlInputElement>().value();
+            draft.set(PlayerProfile { name, ..(*draft).clone() });
+        })
+    };
+    // 再次点击已选中的颜色 / 头像取消选择（颜色改为按蛇ID分配）
+    let pick_color = |color: &'static str| {
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| {
+            let selected = draft.color.as_deref() == Some(color);
+            let color = (!selected).then(|| color.to_string());
+            draft.set(PlayerProfile { color, ..(*draft).clone() });
+        })
+    };
+    let pick_avatar = |avatar: &'static str| {
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| {
+            let selected = draft.avatar.as_deref() == Some(avatar);
+            let avatar = (!selected).then(|| avatar.to_string());
+            draft.set(PlayerProfile { avatar, ..(*draft).clone() });
+        })
+    };
+    let save = {
+        let on_save = on_save.clone();
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| on_save.emit(profile::normalize((*draft).clone())))
+    };
+
+    html! {
+        <div class="profile-editor">
+            <div class="room-form">
+                <input
+                    placeholder="昵称"
+                    maxlength={profile::MAX_NAME_LEN.to_string()}
+                    value={draft.name.clone()}
+                    oninput={on_name}
+                />
+                <button
+                    class="ready-btn"
+                    onclick={save}
+                    disabled={invalid.is_some() || *draft == *current}
+                >{ "保存资料" }</button>
+            </div>
+            <div class="profile-options">
+                { for profile::COLORS.iter().map(|&color| html! {
+                    <button
+                        class={classes!("color-swatch", (draft.color.as_deref() == Some(color)).then_some("selected"))}
+                        style={format!("background: {};", color)}
+                        title={color}
+                        onclick={pick_color(color)}
+                    ></button>
+                }) }
+            </div>
+            <div class="profile-options">
+                { for profile::AVATARS.iter().map(|&avatar| html! {
+                    <button
+                        class={classes!("avatar-btn", (draft.avatar.as_deref() == Some(avatar)).then_some("selected"))}
+                        onclick={pick_avatar(avatar)}
+                    >{ avatar }</button>
+                }) }
+            </div>
+            if let Some(message) = invalid.or_else(|| error.clone()) {
+                <p class="room-error">{ message }</p>
+            }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ProfileEditorProps {
+    pub profile: PlayerProfile, // 服务端已接受的资料
+    #[prop_or_default]
+    pub error: Option<String>,
+    pub on_save: Callback<PlayerProfile>,
+}
+
+// ---------------- 房间大厅组件（创建 / 加入 / 房间列表 / 房间内准备）----------------
+#[function_component(LobbyScreen)]
+pub fn lobby_screen(props: &LobbyScreenProps) -> Html {
+    let LobbyScreenProps { rooms, current, my_id, error, on_create, on_join, on_leave, on_ready, on_refresh } = props;
+    let draft = use_state(RoomSettings::default);
+    let code = use_state(String::new);
+
+    // 已在房间中：显示房间码、成员与准备状态
+    if let Some(room) = current {
+        let me_ready = room.members.iter().any(|m| Some(m.id) == *my_id && m.ready);
+        return html! {
+            <div class="room-lobby">
+                <h3>{ &room.info.name }</h3>
+                <p class="room-code">{ "房间码：" }<strong>{ &room.info.code }</strong></p>
+                if let Some(url) = room_share_url(&room.info.code) {
+                    <p class="share-link">{ "分享链接：" }<input readonly=true value={url} /></p>
+                }
+                <RoomSettingsPanel settings={room.settings.clone()} />
+                <ul class="room-members">
+                    { for room.members.iter().map(|m| html! {
+                        <li class={classes!(m.ready.then_some("ready"))}>
+                            { m.profile.label(m.id) }
+                            if Some(m.id) == *my_id {
+                                { "（你）" }
+                            }
+                            { if m.ready { " - 已准备" } else { " - 未准备" } }
+                        </li>
+                    }) }
+                </ul>
+                <p>
+                    { room.info.players }{ "/" }{ room.info.max_players }
+                    { format!(" 人，全员准备后开始（至少 {} 人）", room.settings.min_players) }
+                </p>
+                <button class="ready-btn" onclick={on_ready.clone()} disabled={me_ready}>
+                    { if me_ready { "已准备" } else { "准备开始" } }
+                </button>
+                <button class="leave-btn" onclick={on_leave.clone()}>{ "离开房间" }</button>
+                if let Some(error) = error {
+                    <p class="room-error">{ error }</p>
+                }
+            </div>
+        };
+    }
+
+    let on_name = {
+        let draft = draft.clone();
+        Callback::from(move |e: InputEvent| {
+            let name = e.target_unchecked_into::<HtmlInputElement>().value();
+            draft.set(RoomSettings { name, ..(*draft).clone() });
+        })
+    };
+    let on_settings = {
+        let draft = draft.clone();
+        Callback::from(move |settings: RoomSettings| draft.set(settings))
+    };
+    // 提交前先在本地校验，与服务端使用同一规则
+    let invalid = settings::validate(&draft).err();
+    let on_code = {
+        let code = code.clone();
+        Callback::from(move |e: InputEvent| code.set(e.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+    let create = {
+        let on_create = on_create.clone();
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| on_create.emit((*draft).clone()))
+    };
+    let join = {
+        let on_join = on_join.clone();
+        let code = code.clone();
+        Callback::from(move |_: MouseEvent| {
+            if !code.trim().is_empty() {
+                on_join.emit(code.trim().to_uppercase());
+            }
+        })
+    };
+
+    html! {
+        <div class="room-lobby">
+            <div class="room-form">
+                <input placeholder="房间名" maxlength={settings::MAX_NAME_LEN.to_string()} value={draft.name.clone()} oninput={on_name} />
+                <button class="ready-btn" onclick={create} disabled={invalid.is_some()}>{ "创建房间" }</button>
+            </div>
+            <RoomSettingsPanel settings={(*draft).clone()} on_change={on_settings} />
+            if let Some(invalid) = invalid {
+                <p class="room-error">{ invalid }</p>
+            }
+            <div class="room-form">
+                <input placeholder="房间码" value={(*code).clone()} oninput={on_code} />
+                <button class="ready-btn" onclick={join}>{ "加入房间" }</button>
+            </div>
+            if let Some(error) = error {
+                <p class="room-error">{ error }</p>
+            }
+            <div class="room-list">
+                <p>
+                    { "房间列表" }
+                    <button class="refresh-btn" onclick={on_refresh.clone()}>{ "刷新" }</button>
+                </p>
+                if rooms.is_empty() {
+                    <p class="room-empty">{ "暂无房间" }</p>
+                }
+                { for rooms.iter().map(|room| {
+                    let on_join = on_join.clone();
+                    let room_code = room.code.clone();
+                    let full = room.players >= room.max_players;
+                    html! {
+                        <div class="room-item">
+                            { format!("{}（{}） {}/{}", room.name, room.code, room.players, room.max_players) }
+                            if room.in_game {
+                                <span class="room-status">{ "对局中" }</span>
+                            } else {
+                                <button
+                                    class="refresh-btn"
+                                    disabled={full}
+                                    onclick={Callback::from(move |_: MouseEvent| on_join.emit(room_code.clone()))}
+                                >{ if full { "已满" } else { "加入" } }</button>
+                            }
+                        </div>
+                    }
+                }) }
+            </div>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct LobbyScreenProps {
+    pub rooms: Vec<RoomInfo>,
+    pub current: Option<RoomDetails>, // 当前所在房间，None 时显示创建 / 加入界面
+    #[prop_or_default]
+    pub my_id: Option<usize>,
+    #[prop_or_default]
+    pub error: Option<String>,
+    pub on_create: Callback<RoomSettings>,
+    pub on_join: Callback<String>, // 参数为房间码
+    pub on_leave: Callback<MouseEvent>,
+    pub on_ready: Callback<MouseEvent>,
+    pub on_refresh: Callback<MouseEvent>,
+}
+
+// ---------------- 房间设置面板（创建房间时编辑，房间内只读展示）----------------
+#[function_component(RoomSettingsPanel)]
+pub fn room_settings_panel(props: &RoomSettingsPanelProps) -> Html {
+    let RoomSettingsPanelProps { settings: current, on_change } = props;
+
+    // 只读：一行摘要
+    let Some(on_change) = on_change else {
+        return html! {
+            <p class="room-settings-summary">
+                { format!(
+                    "{}~{} 人 · {:.1} tick/s · 地图 {}×{} {}{} · 初始长度 {} · 食物 {} · {} · 迎头相撞{}",
+                    current.min_players,
+                    current.max_players,
+                    1000.0 / current.tick_interval_ms as f64,
+                    current.map_width,
+                    current.map_height,
+                    current.layout.name(),
+                    if current.wrap { "（环形）" } else { "" },
+                    current.start_length,
+                    current.food_count,
+                    win_condition_text(&current.win_condition),
+                    current.collision_rule.name(),
+                ) }
+            </p>
+        };
+    };
+
+    // 数字输入：`apply` 把输入值写回设置，超出范围的值交给 `settings::validate` 提示
+    let number = |label: &'static str, value: i64, min: i64, max: i64, apply: fn(&mut RoomSettings, i64)| {
+        let on_change = on_change.clone();
+        let current = current.clone();
+        let oninput = Callback::from(move |e: InputEvent| {
+            if let Ok(value) = e.target_unchecked_into::<HtmlInputElement>().value().parse::<i64>() {
+                let mut next = current.clone();
+                apply(&mut next, value.max(0));
+                on_change.emit(next);
+            }
+        });
+        html! {
+            <label>
+                { label }
+                <input type="number" min={min.to_string()} max={max.to_string()} value={value.to_string()} {oninput} />
+            </label>
+        }
+    };
+
+    let on_layout = {
+        let (on_change, current) = (on_change.clone(), current.clone());
+        Callback::from(move |e: Event| {
+            let index = e.target_unchecked_into::<HtmlSelectElement>().selected_index();
+            let layout = MapLayout::ALL.get(index as usize).copied().unwrap_or_default();
+            on_change.emit(RoomSettings { layout, ..current.clone() });
+        })
+    };
+    let on_wrap = {
+        let (on_change, current) = (on_change.clone(), current.clone());
+        Callback::from(move |_: Event| on_change.emit(RoomSettings { wrap: !current.wrap, ..current.clone() }))
+    };
+    let on_win = {
+        let (on_change, current) = (on_change.clone(), current.clone());
+        Callback::from(move |e: Event| {
+            let win_condition = match e.target_unchecked_into::<HtmlSelectElement>().selected_index() {
+                1 => WinCondition::TargetScore(100),
+                2 => WinCondition::TimeLimit(120),
+                _ => WinCondition::LastAlive,
+            };
+            on_change.emit(RoomSettings { win_condition, ..current.clone() });
+        })
+    };
+
+    let on_collision = {
+        let (on_change, current) = (on_change.clone(), current.clone());
+        Callback::from(move |e: Event| {
+            let index = e.target_unchecked_into::<HtmlSelectElement>().selected_index();
+            let collision_rule = CollisionRule::ALL.get(index as usize).copied().unwrap_or_default();
+            on_change.emit(RoomSettings { collision_rule, ..current.clone() });
+        })
+    };
+
+    let players = settings::PLAYERS;
+    let tick = settings::TICK_INTERVAL_MS;
+    let size = settings::MAP_SIZE;
+    let length = settings::START_LENGTH;
+    let food = settings::FOOD_COUNT;
+    let queue = settings::INPUT_QUEUE_DEPTH;
+    html! {
+        <div class="room-settings">
+            { number("最少人数", current.min_players as i64, *players.start() as i64, *players.end() as i64, |s, v| s.min_players = v as usize) }
+            { number("人数上限", current.max_players as i64, *players.start() as i64, *players.end() as i64, |s, v| s.max_players = v as usize) }
+            { number("tick 间隔（毫秒）", current.tick_interval_ms as i64, *tick.start() as i64, *tick.end() as i64, |s, v| s.tick_interval_ms = v as u32) }
+            { number("地图宽度", current.map_width as i64, *size.start() as i64, *size.end() as i64, |s, v| s.map_width = v as i32) }
+            { number("地图高度", current.map_height as i64, *size.start() as i64, *size.end() as i64, |s, v| s.map_height = v as i32) }
+            { number("初始长度", current.start_length as i64, *length.start() as i64, *length.end() as i64, |s, v| s.start_length = v as usize) }
+            { number("食物数量", current.food_count as i64, *food.start() as i64, *food.end() as i64, |s, v| s.food_count = v as usize) }
+            { number("输入缓冲（步）", current.input_queue_depth as i64, *queue.start() as i64, *queue.end() as i64, |s, v| s.input_queue_depth = v as usize) }
+            <label>
+                { "地图" }
+                <select onchange={on_layout}>
+                    { for MapLayout::ALL.iter().map(|l| html! {
+                        <option selected={*l == current.layout}>{ l.name() }</option>
+                    }) }
+                </select>
+            </label>
+            <label>
+                <input type="checkbox" checked={current.wrap} onchange={on_wrap} />
+                { "环形地图" }
+            </label>
+            <label>
+                { "胜利条件" }
+                <select onchange={on_win}>
+                    <option selected={current.win_condition == WinCondition::LastAlive}>{ "最后存活" }</option>
+                    <option selected={matches!(current.win_condition, WinCondition::TargetScore(_))}>{ "目标分数" }</option>
+                    <option selected={matches!(current.win_condition, WinCondition::TimeLimit(_))}>{ "限时" }</option>
+                </select>
+            </label>
+            { match current.win_condition {
+                WinCondition::LastAlive => html! {},
+                WinCondition::TargetScore(score) => {
+                    let range = settings::TARGET_SCORE;
+                    number("目标分数", score as i64, *range.start() as i64, *range.end() as i64, |s, v| {
+                        s.win_condition = WinCondition::TargetScore(v as u32)
+                    })
+                }
+                WinCondition::TimeLimit(secs) => {
+                    let range = settings::TIME_LIMIT_SECS;
+                    number("限时（秒）", secs as i64, *range.start() as i64, *range.end() as i64, |s, v| {
+                        s.win_condition = WinCondition::TimeLimit(v as u32)
+                    })
+                }
+            } }
+            <label>
+                { "迎头相撞" }
+                <select onchange={on_collision}>
+                    { for CollisionRule::ALL.iter().map(|r| html! {
+                        <option selected={*r == current.collision_rule}>{ r.name() }</option>
+                    }) }
+                </select>
+            </label>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RoomSettingsPanelProps {
+    pub settings: RoomSettings,
+    #[prop_or_default]
+    pub on_change: Option<Callback<RoomSettings>>, // None 时只读
+}
+
+/// 胜利条件的文字说明
+fn win_condition_text(condition: &WinCondition) -> String {
+    match condition {
+        WinCondition::LastAlive => "最后存活".to_string(),
+        WinCondition::TargetScore(score) => format!("先到 {} 分", score),
+        WinCondition::TimeLimit(secs) => format!("限时 {} 秒", secs),
+    }
+}
+
+// ---------------- 连接状态横幅（断线重连提示）----------------
+#[function_component(ConnectionBanner)]
+pub fn connection_banner(props: &ConnectionBannerProps) -> Html {
+    let text = match props.state {
+        ConnectionState::Open => return html! {},
+        ConnectionState::Connecting => "正在连接服务器...".to_string(),
+        ConnectionState::Reconnecting { attempt, delay_ms } => format!(
+            "连接已断开，{:.1} 秒后进行第 {} 次重连...",
+            delay_ms as f64 / 1000.0,
+            attempt
+        ),
+        ConnectionState::Closed => "无法连接服务器，请刷新页面重试".to_string(),
+    };
+    html! {
+        <div class="connection-banner">{ text }</div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ConnectionBannerProps {
+    pub state: ConnectionState,
+}
+
+// ---------------- 网络统计条（延迟、抖动、丢帧）----------------
+#[function_component(NetStatsBar)]
+pub fn net_stats_bar(props: &NetStatsBarProps) -> Html {
+    let NetStatsBarProps { stats, tick_interval_ms } = props;
+    let Some(rtt) = stats.rtt_ms else {
+        return html! {};
+    };
+    html! {
+        <div class="net-stats">
+            { format!("延迟 {:.0} ms · 抖动 {:.1} ms · 丢帧 {}", rtt, stats.jitter_ms, stats.dropped_ticks) }
+            if *tick_interval_ms > 0 {
+                { format!(" · {:.1} tick/s", 1000.0 / *tick_interval_ms as f64) }
+            }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct NetStatsBarProps {
+    pub stats: NetStats,
+    #[prop_or_default]
+    pub tick_interval_ms: u32, // 来自 GameState，0 表示未知
 }
 
 // ---------------- 游戏地图组件（核心，接收后端 GameState 渲染）----------------
 #[function_component(GameMap)]
 pub fn game_map(props: &GameMapProps) -> Html {
-    let GameMapProps { state } = props;
+    let GameMapProps { state, my_id, interpolate } = props;
+    let positions = use_snake_positions(state, *interpolate);
     
     // 从后端状态中获取当前游戏数据
     let (snakes, foods, game_started, game_over) = match state {
         Some(s) => (s.snakes.clone(), s.foods.clone(), s.game_started, s.game_over),
         None => (vec![], vec![], false, false),
     };
+    let walls = state.as_ref().map(|s| s.walls.as_slice()).unwrap_or_default();
+    let dims = map_dims(state);
 
     html! {
         <div class="game-container">
-            <div class="game-map" style="border: 2px solid #333; width: 700px; height: 700px; position: relative; margin: 0 auto;">
+            <div class="game-map" style={board_style(dims, is_wrap(state))}>
+                // 渲染障碍物
+                { for walls.iter().map(|wall| render_wall(wall, dims)) }
                 // 渲染所有玩家的蛇（不同蛇用不同颜色区分）
-                { for snakes.iter().map(|snake| render_snake(snake)) }
+                { for snakes.iter().zip(&positions).map(|(snake, body)| render_snake(snake, body, dims, Some(snake.id) == *my_id)) }
                 // 渲染食物
-                { for foods.iter().map(|food| render_food(food)) }
+                { for foods.iter().map(|food| render_food(food, dims)) }
                 // 游戏未开始提示
                 if !game_started && snakes.is_empty() {
                     <div class="game-tip">{ "等待玩家加入..." }</div>
@@ -111,20 +837,230 @@ pub fn game_map(props: &GameMapProps) -> Html {
 #[derive(Properties, PartialEq)]
 pub struct GameMapProps {
     pub state: Option<GameState>, // 接收后端传递的游戏状态
+    #[prop_or_default]
+    pub my_id: Option<usize>, // 本地玩家的蛇ID（用于高亮自己的蛇）
+    #[prop_or_default]
+    pub interpolate: bool, // 是否在 tick 之间平滑插值
+}
+
+/// 地图宽高（格），尚未收到状态时使用默认尺寸
+pub(crate) fn map_dims(state: &Option<GameState>) -> (i32, i32) {
+    state
+        .as_ref()
+        .map_or((DEFAULT_MAP_WIDTH, DEFAULT_MAP_HEIGHT), |s| (s.map_width, s.map_height))
+}
+
+/// 是否为环形地图
+pub(crate) fn is_wrap(state: &Option<GameState>) -> bool {
+    state.as_ref().is_some_and(|s| s.wrap)
+}
+
+/// 地图容器样式：保持宽高比，并按视口缩放（手机上不超出屏幕宽度，桌面上不超出屏幕高度）；
+/// 环形地图用虚线边框表示边缘可以穿越
+pub(crate) fn board_style((width, height): (i32, i32), wrap: bool) -> String {
+    let border = if wrap { "dashed" } else { "solid" };
+    format!(
+        "border: 2px {border} #333; width: min(96vw, calc(75vh * {width} / {height})); aspect-ratio: {width} / {height}; position: relative; overflow: hidden;"
+    )
+}
+
+/// 单个格子的定位样式：按地图宽高换算成百分比，随地图容器缩放
+fn cell_style(x: f64, y: f64, (width, height): (i32, i32)) -> String {
+    format!(
+        "position: absolute; left: {:.3}%; top: {:.3}%; width: {:.3}%; height: {:.3}%;",
+        x * 100.0 / width as f64,
+        y * 100.0 / height as f64,
+        100.0 / width as f64,
+        100.0 / height as f64
+    )
+}
+
+/// 每条蛇各节的渲染坐标（格，与 `state.snakes` 顺序一致），DOM 与 Canvas 渲染共用
+///
+/// 插值模式下记录上一帧与当前帧状态，每个动画帧按当前帧到达后经过的时间插值；
+/// 不插值时即为整数格坐标。
+#[hook]
+pub(crate) fn use_snake_positions(state: &Option<GameState>, interpolate: bool) -> Vec<Vec<(f64, f64)>> {
+    let frames = use_mut_ref(InterpolationFrames::default);
+    // 只在对局进行中逐帧重绘，大厅与结算界面不需要
+    let running = state.as_ref().is_some_and(|s| s.game_started && !s.game_over);
+    use_animation_frame(interpolate && running, frames.clone());
+
+    let now = js_sys::Date::now();
+    let mut frames = frames.borrow_mut();
+    if frames.curr != *state {
+        frames.prev = frames.curr.take();
+        frames.curr = state.clone();
+        frames.arrived_at = now;
+    }
+    let progress = frames.progress(now);
+
+    let snakes = state.as_ref().map(|s| s.snakes.as_slice()).unwrap_or_default();
+    snakes
+        .iter()
+        .map(|snake| {
+            let prev = frames
+                .prev
+                .as_ref()
+                .and_then(|p| p.snakes.iter().find(|s| s.id == snake.id));
+            match (interpolate, progress, prev) {
+                (true, Some(t), Some(prev)) => interpolate_body(prev, snake, t),
+                _ => snake.body.iter().map(|p| (p.x as f64, p.y as f64)).collect(),
+            }
+        })
+        .collect()
+}
+
+// 插值所需的前后两帧状态
+#[derive(Default)]
+struct InterpolationFrames {
+    prev: Option<GameState>,
+    curr: Option<GameState>,
+    arrived_at: f64,
+}
+
+impl InterpolationFrames {
+    /// 当前帧到达后经过的比例（0~1）；两帧不连续（换房间、跳帧）时返回 None，直接显示当前帧
+    fn progress(&self, now: f64) -> Option<f64> {
+        let (prev, curr) = (self.prev.as_ref()?, self.curr.as_ref()?);
+        if prev.room_id != curr.room_id || !curr.game_started || curr.tick > prev.tick + 1 {
+            return None;
+        }
+        let interval = if curr.tick_interval_ms > 0 { curr.tick_interval_ms } else { 150 };
+        Some(((now - self.arrived_at) / interval as f64).clamp(0.0, 1.0))
+    }
+}
+
+// 动画帧回调（需要在回调内部再次注册自身）
+type FrameCallback = Rc<RefCell<Option<Closure<dyn FnMut(f64)>>>>;
+
+/// 插值进行中的每个动画帧触发一次重新渲染（`enabled` 为 false 时不启动）；
+/// 当前帧已经移动到位（进度到 1）后不再重绘，直到下一个状态到达
+#[hook]
+fn use_animation_frame(enabled: bool, frames: Rc<RefCell<InterpolationFrames>>) {
+    let frame = use_state(|| 0u64);
+    use_effect_with(enabled, move |&enabled| {
+        let callback: FrameCallback = Rc::new(RefCell::new(None));
+        let handle = Rc::new(Cell::new(None::<i32>));
+        if enabled {
+            let callback_inner = callback.clone();
+            let handle_inner = handle.clone();
+            let mut count = 0u64;
+            *callback.borrow_mut() = Some(Closure::wrap(Box::new(move |_timestamp: f64| {
+                let moving = frames
+                    .borrow()
+                    .progress(js_sys::Date::now())
+                    .is_some_and(|t| t < 1.0);
+                if moving {
+                    count += 1;
+                    frame.set(count);
+                }
+                if let Some(cb) = callback_inner.borrow().as_ref() {
+                    handle_inner.set(request_frame(cb));
+                }
+            }) as Box<dyn FnMut(f64)>));
+            if let Some(cb) = callback.borrow().as_ref() {
+                handle.set(request_frame(cb));
+            }
+        }
+        move || {
+            if let (Some(w), Some(id)) = (window(), handle.get()) {
+                let _ = w.cancel_animation_frame(id);
+            }
+            callback.borrow_mut().take();
+        }
+    });
+}
+
+fn request_frame(callback: &Closure<dyn FnMut(f64)>) -> Option<i32> {
+    window()?
+        .request_animation_frame(callback.as_ref().unchecked_ref())
+        .ok()
+}
+
+/// 对蛇身每一节在上一帧与当前帧之间插值（第 i 节从上一帧的第 i 节移动过来，新长出的蛇尾原地不动）
+fn interpolate_body(prev: &Snake, curr: &Snake, t: f64) -> Vec<(f64, f64)> {
+    curr.body
+        .iter()
+        .enumerate()
+        .map(|(i, to)| match prev.body.get(i).or(prev.body.last()) {
+            Some(from) => lerp_cell(*from, *to, t),
+            None => (to.x as f64, to.y as f64),
+        })
+        .collect()
+}
+
+/// 单格插值：相邻格正常移动；同一轴上跨越多格视为穿越边界，从对侧边缘外一格滑入；
+/// 两个轴都变化视为传送（重生等），直接显示在新位置
+fn lerp_cell(from: Position, to: Position, t: f64) -> (f64, f64) {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let from = match (dx.abs(), dy.abs()) {
+        (0, 0) | (1, 0) | (0, 1) => from,
+        (_, 0) => Position::new(to.x + dx.signum(), to.y),
+        (0, _) => Position::new(to.x, to.y + dy.signum()),
+        _ => to,
+    };
+    (
+        from.x as f64 + (to.x - from.x) as f64 * t,
+        from.y as f64 + (to.y - from.y) as f64 * t,
+    )
+}
+
+// ---------------- 击杀播报（对局中显示最近的死亡事件）----------------
+#[function_component(KillFeedView)]
+pub fn kill_feed_view(props: &KillFeedViewProps) -> Html {
+    if props.entries.is_empty() {
+        return html! {};
+    }
+    html! {
+        <div class="kill-feed">
+            { for props.entries.iter().map(|entry| {
+                let victim = html! {
+                    <span style={format!("color: {};", entry.victim_color)}>{ &entry.victim }</span>
+                };
+                html! {
+                    <div class="kill-feed-item">
+                        if let Some((killer, color)) = &entry.killer {
+                            <span style={format!("color: {};", color)}>{ killer }</span>
+                            { " 撞死了 " }{ victim }
+                        } else {
+                            { victim }{ " " }{ entry.cause.name() }
+                        }
+                    </div>
+                }
+            }) }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct KillFeedViewProps {
+    pub entries: Vec<KillFeedEntry>,
 }
 
 // ---------------- 游戏结束排名组件（原 GameOver）----------------
 #[function_component(GameOver)]
 pub fn game_over(props: &GameOverProps) -> Html {
-    let GameOverProps { rankings, on_restart } = props;
+    let GameOverProps { rankings, on_restart, my_id, profiles } = props;
+    // 本地玩家的名次（从 1 开始）
+    let my_place = my_id.and_then(|id| rankings.iter().position(|(snake_id, _)| *snake_id == id));
     html! {
         <div class="game-over-modal">
             <h3>{ "游戏结束" }</h3>
+            if let Some(place) = my_place {
+                <p class="my-place">{ "你获得了第" }{ place + 1 }{ "名" }</p>
+            }
             <div class="rankings">
                 { rankings.iter().enumerate().map(|(i, (snake_id, score))| {
+                    let is_me = Some(*snake_id) == *my_id;
+                    let name = profiles.get(snake_id).cloned().unwrap_or_default().label(*snake_id);
                     html! {
-                        <div class="rank-item">
-                            { "第" }{ i + 1 }{ "名: 蛇" }{ snake_id }{ " - " }{ score }{ "分" }
+                        <div class={classes!("rank-item", is_me.then_some("me"))}>
+                            { "第" }{ i + 1 }{ "名: " }{ name }
+                            if is_me {
+                                { "（你）" }
+                            }
+                            { " - " }{ score }{ "分" }
                         </div>
                     }
                 }).collect::<Html>() }
@@ -138,59 +1074,100 @@ pub fn game_over(props: &GameOverProps) -> Html {
 pub struct GameOverProps {
     pub rankings: Vec<(usize, u32)>,
     pub on_restart: Callback<MouseEvent>,
+    #[prop_or_default]
+    pub my_id: Option<usize>,
+    // 参与本局的玩家资料（来自最后一帧状态），缺失时显示"蛇{id}"
+    #[prop_or_default]
+    pub profiles: HashMap<usize, PlayerProfile>,
 }
 
 // ---------------- 辅助渲染函数 ----------------
-/// 渲染单条蛇（不同蛇ID对应不同颜色）
-fn render_snake(snake: &Snake) -> Html {
-    let color = get_snake_color(snake.id); // 根据蛇ID生成唯一颜色
+/// 渲染单条蛇（使用玩家选择的颜色，未选择时按蛇ID分配；自己的蛇加描边高亮）
+/// `positions` 为各节的格坐标，插值模式下可以是小数
+fn render_snake(snake: &Snake, positions: &[(f64, f64)], dims: (i32, i32), is_me: bool) -> Html {
+    let color = snake_color(snake);
+    let highlight = if is_me { " box-shadow: 0 0 0 2px #222; z-index: 1;" } else { "" };
     html! {
         <>
-        { for snake.body.iter().enumerate().map(|(idx, segment)| {
+        { for positions.iter().enumerate().map(|(idx, (x, y))| {
             // 蛇头用深色，身体用浅色
             let bg_color = if idx == 0 { darken_color(color.clone()) } else { color.clone() };
             html! {
                 <div 
                     key={format!("snake-{}-{}", snake.id, idx)}
                     style={format!(
-                        "position: absolute; width: 20px; height: 20px; background: {}; border-radius: 3px; left: {}px; top: {}px;",
+                        "{} background: {}; border-radius: 3px;{}",
+                        cell_style(*x, *y, dims),
                         bg_color,
-                        segment.x * 20, // 地图格子大小：20px/格
-                        segment.y * 20
+                        highlight
                     )}
                 ></div>
             }
         })}
+        // 蛇头上方显示头像与昵称，自己的蛇额外标记"我"
+        if let Some((x, y)) = positions.first() {
+            <div
+                class={classes!("snake-label", is_me.then_some("me-label"))}
+                style={format!(
+                    "left: {:.3}%; top: calc({:.3}% - 18px); width: {:.3}%;",
+                    x * 100.0 / dims.0 as f64,
+                    y * 100.0 / dims.1 as f64,
+                    100.0 / dims.0 as f64
+                )}
+            >{ snake_label(snake, is_me) }</div>
+        }
         </>
     }
 }
 
 /// 渲染单个食物
-fn render_food(food: &Food) -> Html {
+fn render_food(food: &Food, dims: (i32, i32)) -> Html {
+    let (x, y) = (food.position.x as f64, food.position.y as f64);
     html! {
         <div
             key={format!("food-{}-{}", food.position.x, food.position.y)}
-            style={format!(
-                "position: absolute; width: 20px; height: 20px; background: #ff3333; border-radius: 50%; left: {}px; top: {}px;",
-                food.position.x * 20,
-                food.position.y * 20
-            )}
+            style={format!("{} background: #ff3333; border-radius: 50%;", cell_style(x, y, dims))}
+        ></div>
+    }
+}
+
+/// 障碍物颜色
+pub(crate) const WALL_COLOR: &str = "#555";
+
+/// 渲染单个障碍物格子
+fn render_wall(wall: &Position, dims: (i32, i32)) -> Html {
+    html! {
+        <div
+            key={format!("wall-{}-{}", wall.x, wall.y)}
+            style={format!("{} background: {};", cell_style(wall.x as f64, wall.y as f64, dims), WALL_COLOR)}
         ></div>
     }
 }
 
 /// 根据蛇ID生成唯一颜色（避免重复）
-fn get_snake_color(snake_id: usize) -> String {
-    let colors = [
-        "#4CAF50", "#2196F3", "#FFC107", "#9C27B0", "#FF9800", 
-        "#00BCD4", "#8BC34A", "#FF5722", "#607D8B", "#795548"
-    ];
+pub(crate) fn get_snake_color(snake_id: usize) -> String {
+    let colors = profile::COLORS;
     let color_idx = snake_id % colors.len();
     colors[color_idx].to_string()
 }
 
+/// 蛇的颜色：玩家选择的颜色优先
+pub(crate) fn snake_color(snake: &Snake) -> String {
+    snake.profile.color.clone().unwrap_or_else(|| get_snake_color(snake.id))
+}
+
+/// 蛇头上方的标签：头像与昵称，自己的蛇加"（我）"
+pub(crate) fn snake_label(snake: &Snake, is_me: bool) -> String {
+    let label = snake.profile.label(snake.id);
+    if is_me {
+        format!("{}（我）", label)
+    } else {
+        label
+    }
+}
+
 /// 加深颜色（用于蛇头）
-fn darken_color(color: String) -> String {
+pub(crate) fn darken_color(color: String) -> String {
     // 简单实现：将RGB值减少30（确保不小于0）
     let r = i32::from_str_radix(&color[1..3], 16).unwrap_or(0);
     let g = i32::from_str_radix(&color[3..5], 16).unwrap_or(0);
@@ -208,6 +1185,8 @@ pub fn styles() -> &'static str {
     r#"
         .game-container {
             margin: 20px 0;
+            display: flex;
+            justify-content: center;
         }
         .game-tip {
             position: absolute;
@@ -229,6 +1208,23 @@ pub fn styles() -> &'static str {
             padding: 10px 20px;
             border-radius: 8px;
         }
+        .connection-banner {
+            margin: 10px 0;
+            padding: 8px 16px;
+            background: #FFF3CD;
+            color: #856404;
+            border: 1px solid #FFE08A;
+            border-radius: 4px;
+        }
+        .smooth-toggle {
+            font-size: 14px;
+            color: #666;
+            cursor: pointer;
+        }
+        .net-stats {
+            font-size: 12px;
+            color: #888;
+        }
         .matching {
             margin: 20px 0;
             padding: 20px;
@@ -250,6 +1246,76 @@ pub fn styles() -> &'static str {
             background: #cccccc;
             cursor: not-allowed;
         }
+        .room-lobby {
+            margin: 20px 0;
+            padding: 20px;
+            border: 1px solid #ddd;
+            border-radius: 8px;
+            text-align: center;
+        }
+        .room-form {
+            display: flex;
+            gap: 8px;
+            justify-content: center;
+            align-items: center;
+            flex-wrap: wrap;
+            margin-bottom: 10px;
+        }
+        .room-form input:not([type="checkbox"]) {
+            padding: 6px;
+        }
+        .room-form .ready-btn {
+            margin-top: 0;
+        }
+        .room-settings {
+            display: grid;
+            grid-template-columns: repeat(auto-fill, minmax(140px, 1fr));
+            gap: 8px;
+            margin-bottom: 10px;
+            text-align: left;
+            font-size: 13px;
+        }
+        .room-settings input[type="number"], .room-settings select {
+            display: block;
+            width: 100%;
+            box-sizing: border-box;
+        }
+        .room-settings-summary {
+            font-size: 13px;
+            color: #555;
+        }
+        .room-code strong {
+            font-size: 22px;
+            letter-spacing: 3px;
+        }
+        .share-link input {
+            width: 80%;
+        }
+        .room-members {
+            list-style: none;
+            padding: 0;
+        }
+        .room-members li.ready {
+            color: #4CAF50;
+        }
+        .room-item {
+            display: flex;
+            justify-content: space-between;
+            align-items: center;
+            padding: 6px 0;
+            border-top: 1px solid #eee;
+        }
+        .room-status, .room-empty {
+            color: #888;
+        }
+        .room-error {
+            color: #d32f2f;
+        }
+        .leave-btn, .refresh-btn {
+            padding: 4px 12px;
+            margin-left: 8px;
+            cursor: pointer;
+        }
         .game-over-modal {
             position: fixed;
             top: 50%;
@@ -270,6 +1336,90 @@ pub fn styles() -> &'static str {
             margin: 10px 0;
             font-size: 18px;
         }
+        .rank-item.me {
+            font-weight: bold;
+            color: #2196F3;
+        }
+        .my-place {
+            font-size: 20px;
+            color: #2196F3;
+        }
+        .snake-label {
+            position: absolute;
+            font-size: 11px;
+            color: #444;
+            text-align: center;
+            white-space: nowrap;
+            display: flex;
+            justify-content: center;
+            pointer-events: none;
+            z-index: 2;
+        }
+        .me-label {
+            font-size: 12px;
+            font-weight: bold;
+            color: #222;
+        }
+        .leaderboard {
+            margin: 10px auto;
+            max-width: 420px;
+        }
+        .leaderboard .selected {
+            background: #2196F3;
+            color: white;
+        }
+        .leaderboard-table {
+            width: 100%;
+            border-collapse: collapse;
+            font-size: 14px;
+        }
+        .leaderboard-table th, .leaderboard-table td {
+            padding: 4px;
+            border-bottom: 1px solid #eee;
+        }
+        .kill-feed {
+            margin: 6px auto;
+            max-width: 420px;
+            font-size: 14px;
+            text-align: right;
+        }
+        .kill-feed-item {
+            padding: 2px 0;
+        }
+        .stats-summary {
+            display: flex;
+            flex-wrap: wrap;
+            gap: 12px;
+            justify-content: center;
+            margin: 8px 0;
+        }
+        .account-panel {
+            margin: 10px auto;
+            max-width: 420px;
+        }
+        .profile-editor {
+            margin: 10px auto;
+            max-width: 420px;
+        }
+        .profile-options {
+            display: flex;
+            flex-wrap: wrap;
+            justify-content: center;
+            gap: 6px;
+            margin: 6px 0;
+        }
+        .color-swatch, .avatar-btn {
+            width: 28px;
+            height: 28px;
+            padding: 0;
+            border: 2px solid transparent;
+            border-radius: 50%;
+            background: #f0f0f0;
+            cursor: pointer;
+        }
+        .color-swatch.selected, .avatar-btn.selected {
+            border-color: #222;
+        }
         /* 虚拟键盘样式 */
         .virtual-keyboard {
             margin: 20px auto;
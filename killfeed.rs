@@ -0,0 +1,70 @@
+// killfeed.rs
+// 击杀播报：从每个 tick 下发的事件中收集最近的死亡事件
+use std::collections::VecDeque;
+use crate::game::snake_color;
+use crate::types::{DeathCause, GameEvent, GameState};
+
+/// 最多显示的播报条数
+pub const KILL_FEED_SIZE: usize = 5;
+
+/// 一条播报：名字与颜色在记录时取自当时的状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct KillFeedEntry {
+    pub tick: u64,
+    pub victim: String,
+    pub victim_color: String,
+    // 击杀者的名字与颜色（撞到其他蛇时）
+    pub killer: Option<(String, String)>,
+    pub cause: DeathCause,
+}
+
+/// 击杀播报记录
+///
+/// 同一 tick 的状态可能被重复收到（例如请求快照后），只记录 tick 前进时的事件；
+/// 进入新的一局时清空。
+#[derive(Debug, Clone, Default)]
+pub struct KillFeed {
+    room_id: String,
+    last_tick: u64,
+    entries: VecDeque<KillFeedEntry>,
+}
+
+impl KillFeed {
+    /// 收到权威状态：记录其中的死亡事件
+    pub fn record(&mut self, state: &GameState) {
+        if state.room_id != self.room_id {
+            self.room_id = state.room_id.clone();
+            self.last_tick = 0;
+            self.entries.clear();
+        }
+        if state.tick <= self.last_tick {
+            return;
+        }
+        self.last_tick = state.tick;
+
+        let find = |id: usize| state.snakes.iter().find(|s| s.id == id);
+        for event in &state.events {
+            let GameEvent::Died { id, cause, killer } = *event else {
+                continue;
+            };
+            let Some(victim) = find(id) else {
+                continue;
+            };
+            self.entries.push_back(KillFeedEntry {
+                tick: state.tick,
+                victim: victim.profile.label(id),
+                victim_color: snake_color(victim),
+                killer: killer.and_then(find).map(|k| (k.profile.label(k.id), snake_color(k))),
+                cause,
+            });
+        }
+        while self.entries.len() > KILL_FEED_SIZE {
+            self.entries.pop_front();
+        }
+    }
+
+    /// 最近的播报，新的在后
+    pub fn entries(&self) -> Vec<KillFeedEntry> {
+        self.entries.iter().cloned().collect()
+    }
+}
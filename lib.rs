@@ -1,31 +1,95 @@
 // lib.rs
+use std::collections::HashMap;
 use yew::prelude::*;
 use web_sys::KeyboardEvent;
 pub mod game;
 pub mod websocket;
 pub mod types;
-use game::{GameMap, MatchingStatus, GameOver, VirtualKeyboard, styles};
-use websocket::WsClient;
-use types::{GameMessage, Direction, GameState};
+pub mod engine;
+pub mod config;
+pub mod codec;
+pub mod delta;
+pub mod prediction;
+pub mod canvas;
+pub mod map;
+pub mod settings;
+pub mod profile;
+pub mod killfeed;
+use game::{GameMap, MatchingStatus, GameOver, VirtualKeyboard, ConnectionBanner, NetStatsBar, LobbyScreen, ProfileEditor, AccountPanel, LeaderboardView, StatsView, KillFeedView, styles};
+use websocket::{WsClient, ConnectionState, NetStats};
+use config::{server_url, room_code, saved_profile, save_profile};
+use prediction::Predictor;
+use killfeed::KillFeed;
+use canvas::GameCanvas;
+use types::{
+    GameMessage, Direction, GameState, RoomInfo, RoomDetails, RoomSettings, PlayerProfile,
+    GameMode, Leaderboard, LeaderboardPeriod, PlayerStats,
+};
 
+/// 应用根组件（main.rs 挂载到页面）
 #[function_component(App)]
-fn app() -> Html {
+pub fn app() -> Html {
     let ws_client = use_state(|| None::<WsClient>);
     let game_state = use_state(|| None::<GameState>);
     let matching_status = use_state(|| (0, 2));
     let game_over_rankings = use_state(|| None::<Vec<(usize, u32)>>);
     let is_ready = use_state(|| false);
+    let connection_state = use_state(|| ConnectionState::Connecting);
+    let my_id = use_state(|| None::<usize>);
+    let net_stats = use_state(NetStats::default);
+    // 渲染用状态：权威状态叠加本地蛇的预测
+    let display_state = use_state(|| None::<GameState>);
+    let predictor = use_mut_ref(Predictor::default);
+    let kill_feed = use_mut_ref(KillFeed::default);
+    let smooth = use_state(|| true);
+    let use_canvas = use_state(|| false);
+    let rooms = use_state(Vec::<RoomInfo>::new);
+    let current_room = use_state(|| None::<RoomDetails>);
+    let room_error = use_state(|| None::<String>);
+    let rating = use_state(|| None::<(u32, u32)>);
+    // 服务端接受的玩家资料（先用本地保存的资料，连接后由服务端确认）
+    let profile = use_state(|| saved_profile().unwrap_or_default());
+    let profile_error = use_state(|| None::<String>);
+    // 已登录的用户名
+    let account = use_state(|| None::<String>);
+    let auth_error = use_state(|| None::<String>);
+    let leaderboard = use_state(|| None::<Leaderboard>);
+    let stats = use_state(|| None::<PlayerStats>);
 
     {
         let ws_client = ws_client.clone();
         let game_state_clone = game_state.clone();
         let matching_status_clone = matching_status.clone();
         let game_over_rankings_clone = game_over_rankings.clone();
+        let connection_state_clone = connection_state.clone();
+        let my_id_clone = my_id.clone();
+        let net_stats_clone = net_stats.clone();
+        let display_state_clone = display_state.clone();
+        let predictor_clone = predictor.clone();
+        let kill_feed_clone = kill_feed.clone();
+        let predictor_welcome = predictor.clone();
+        let rooms_clone = rooms.clone();
+        let current_room_clone = current_room.clone();
+        let room_error_clone = room_error.clone();
+        let room_error_update = room_error.clone();
+        let rating_clone = rating.clone();
+        let profile_clone = profile.clone();
+        let profile_error_clone = profile_error.clone();
+        let profile_error_accepted = profile_error.clone();
+        let account_clone = account.clone();
+        let auth_error_clone = auth_error.clone();
+        let auth_error_account = auth_error.clone();
+        let leaderboard_clone = leaderboard.clone();
+        let stats_clone = stats.clone();
+        let is_ready_clone = is_ready.clone();
         
         use_effect_with((), move |_| {
-            let mut client = WsClient::new("ws://47.100.220.180:3000/ws");
+            let mut client = WsClient::new(&server_url());
             
             let game_state_cb = Callback::from(move |state: GameState| {
+                kill_feed_clone.borrow_mut().record(&state);
+                predictor_clone.borrow_mut().on_server_state(state.clone());
+                display_state_clone.set(predictor_clone.borrow().predicted());
                 game_state_clone.set(Some(state));
             });
             client = client.on_game_state(game_state_cb);
@@ -39,6 +103,79 @@ fn app() -> Html {
                 game_over_rankings_clone.set(Some(rankings));
             });
             client = client.on_game_over(game_over_cb);
+
+            let welcome_cb = Callback::from(move |(player_id, _room_id): (usize, String)| {
+                predictor_welcome.borrow_mut().set_player(player_id);
+                my_id_clone.set(Some(player_id));
+            });
+            client = client.on_welcome(welcome_cb);
+
+            let net_stats_cb = Callback::from(move |stats: NetStats| {
+                net_stats_clone.set(stats);
+            });
+            client = client.on_net_stats(net_stats_cb);
+
+            let connection_cb = Callback::from(move |state: ConnectionState| {
+                connection_state_clone.set(state);
+            });
+            client = client.on_connection_state(connection_cb);
+
+            let room_list_cb = Callback::from(move |list: Vec<RoomInfo>| {
+                rooms_clone.set(list);
+            });
+            client = client.on_room_list(room_list_cb);
+
+            let room_update_cb = Callback::from(move |room: Option<RoomDetails>| {
+                if room.is_some() {
+                    is_ready_clone.set(false);
+                }
+                room_error_update.set(None);
+                current_room_clone.set(room);
+            });
+            client = client.on_room_update(room_update_cb);
+
+            let room_error_cb = Callback::from(move |message: String| {
+                room_error_clone.set(Some(message));
+            });
+            client = client.on_room_error(room_error_cb);
+
+            let rating_cb = Callback::from(move |value: (u32, u32)| {
+                rating_clone.set(Some(value));
+            });
+            client = client.on_rating(rating_cb);
+
+            let profile_cb = Callback::from(move |accepted: PlayerProfile| {
+                save_profile(&accepted);
+                profile_error_accepted.set(None);
+                profile_clone.set(accepted);
+            });
+            client = client.on_profile(profile_cb);
+
+            let profile_error_cb = Callback::from(move |message: String| {
+                profile_error_clone.set(Some(message));
+            });
+            client = client.on_profile_error(profile_error_cb);
+
+            let account_cb = Callback::from(move |name: Option<String>| {
+                auth_error_account.set(None);
+                account_clone.set(name);
+            });
+            client = client.on_account(account_cb);
+
+            let auth_error_cb = Callback::from(move |message: String| {
+                auth_error_clone.set(Some(message));
+            });
+            client = client.on_auth_error(auth_error_cb);
+
+            let leaderboard_cb = Callback::from(move |board: Leaderboard| {
+                leaderboard_clone.set(Some(board));
+            });
+            client = client.on_leaderboard(leaderboard_cb);
+
+            let stats_cb = Callback::from(move |player_stats: PlayerStats| {
+                stats_clone.set(Some(player_stats));
+            });
+            client = client.on_stats(stats_cb);
             
             client.start_listening();
             ws_client.set(Some(client));
@@ -63,6 +200,34 @@ fn app() -> Html {
         });
     }
 
+    // 首次连接时获取房间列表并加入分享链接中的房间；
+    // 断线重连成功后重新加入原来的房间，或在已准备时重新加入匹配
+    {
+        let ws_client = ws_client.clone();
+        let is_ready = is_ready.clone();
+        let game_over_rankings = game_over_rankings.clone();
+        let current_room = current_room.clone();
+        let has_connected = use_mut_ref(|| false);
+        use_effect_with(*connection_state, move |state| {
+            if *state == ConnectionState::Open {
+                let reconnected = std::mem::replace(&mut *has_connected.borrow_mut(), true);
+                if let Some(client) = &*ws_client {
+                    if !reconnected {
+                        client.send(GameMessage::ListRooms);
+                        if let Some(code) = room_code() {
+                            client.send(GameMessage::JoinRoom { code });
+                        }
+                    } else if let Some(room) = &*current_room {
+                        client.send(GameMessage::JoinRoom { code: room.info.code.clone() });
+                    } else if *is_ready && game_over_rankings.is_none() {
+                        client.send(GameMessage::Ready);
+                    }
+                }
+            }
+            || ()
+        });
+    }
+
     let send_message = {
         let ws_client = ws_client.clone();
         move |msg: GameMessage| {
@@ -92,63 +257,202 @@ fn app() -> Html {
         })
     };
 
-    // 处理虚拟键盘方向输入
-    let handle_virtual_direction = {
+    // 房间操作：创建、加入、离开、刷新列表、房间内准备
+    let handle_create_room = {
         let send_message = send_message.clone();
-        let game_state = game_state.clone();
-        Callback::from(move |direction: Direction| {
-            let state = game_state.as_ref();
-            if state.map_or(true, |s| !s.game_started || s.game_over) {
-                return;
-            }
-            send_message(GameMessage::PlayerInput(direction));
+        Callback::from(move |settings: RoomSettings| send_message(GameMessage::CreateRoom { settings }))
+    };
+
+    let handle_login = {
+        let send_message = send_message.clone();
+        Callback::from(move |(username, password): (String, String)| {
+            send_message(GameMessage::Login { username, password })
         })
     };
 
-    let handle_keydown = {
+    let handle_register = {
+        let send_message = send_message.clone();
+        Callback::from(move |(username, password): (String, String)| {
+            send_message(GameMessage::Register { username, password })
+        })
+    };
+
+    let handle_logout = {
+        let send_message = send_message.clone();
+        Callback::from(move |_: MouseEvent| send_message(GameMessage::Logout))
+    };
+
+    let handle_leaderboard_query = {
+        let send_message = send_message.clone();
+        Callback::from(move |(period, mode): (LeaderboardPeriod, Option<GameMode>)| {
+            send_message(GameMessage::GetLeaderboard { period, mode })
+        })
+    };
+
+    let handle_stats_query = {
+        let send_message = send_message.clone();
+        Callback::from(move |_: ()| send_message(GameMessage::GetStats))
+    };
+
+    let handle_save_profile = {
+        let send_message = send_message.clone();
+        Callback::from(move |profile: PlayerProfile| send_message(GameMessage::SetProfile { profile }))
+    };
+
+    let handle_join_room = {
+        let send_message = send_message.clone();
+        Callback::from(move |code: String| send_message(GameMessage::JoinRoom { code }))
+    };
+
+    let handle_leave_room = {
+        let send_message = send_message.clone();
+        Callback::from(move |_: MouseEvent| {
+            send_message(GameMessage::LeaveRoom);
+            send_message(GameMessage::ListRooms);
+        })
+    };
+
+    let handle_refresh_rooms = {
+        let send_message = send_message.clone();
+        Callback::from(move |_: MouseEvent| send_message(GameMessage::ListRooms))
+    };
+
+    let handle_room_ready = {
+        let send_message = send_message.clone();
+        Callback::from(move |_: MouseEvent| send_message(GameMessage::Ready))
+    };
+
+    // 发送方向输入：记录到预测器（输入队列）并立即更新本地蛇，再带序号发送给服务端
+    let send_input = {
         let send_message = send_message.clone();
         let game_state = game_state.clone();
-        Callback::from(move |e: KeyboardEvent| {
+        let display_state = display_state.clone();
+        let predictor = predictor.clone();
+        move |direction: Direction| {
             let state = game_state.as_ref();
             if state.map_or(true, |s| !s.game_started || s.game_over) {
                 return;
             }
+            // 反向、重复或超出输入缓冲的转向在本地丢弃
+            let Some(seq) = predictor.borrow_mut().input(direction) else {
+                return;
+            };
+            display_state.set(predictor.borrow().predicted());
+            send_message(GameMessage::SequencedInput { seq, direction });
+        }
+    };
+
+    // 处理虚拟键盘方向输入
+    let handle_virtual_direction = {
+        let send_input = send_input.clone();
+        Callback::from(move |direction: Direction| send_input(direction))
+    };
+
+    let handle_keydown = {
+        let send_input = send_input.clone();
+        Callback::from(move |e: KeyboardEvent| {
             match e.key().as_str() {
-                "ArrowUp" => send_message(GameMessage::PlayerInput(Direction::Up)),
-                "ArrowDown" => send_message(GameMessage::PlayerInput(Direction::Down)),
-                "ArrowLeft" => send_message(GameMessage::PlayerInput(Direction::Left)),
-                "ArrowRight" => send_message(GameMessage::PlayerInput(Direction::Right)),
+                "ArrowUp" => send_input(Direction::Up),
+                "ArrowDown" => send_input(Direction::Down),
+                "ArrowLeft" => send_input(Direction::Left),
+                "ArrowRight" => send_input(Direction::Right),
                 _ => {}
             }
         })
     };
 
+    let toggle_smooth = {
+        let smooth = smooth.clone();
+        Callback::from(move |_: Event| smooth.set(!*smooth))
+    };
+
+    let toggle_canvas = {
+        let use_canvas = use_canvas.clone();
+        Callback::from(move |_: Event| use_canvas.set(!*use_canvas))
+    };
+
     // 修复：用嵌套 if 替代 let chain 语法
     let show_matching = {
         let game_state = game_state.clone();
         game_state.as_ref().map_or(false, |s| !s.game_started)
     };
 
-    // 检查游戏是否正在进行（显示虚拟键盘的条件）
+    // 检查游戏是否正在进行（显示虚拟键盘与击杀播报的条件）
     let show_virtual_keyboard = {
         let game_state = game_state.clone();
         game_state.as_ref().map_or(false, |s| s.game_started && !s.game_over)
     };
 
+    // 排名中显示的玩家资料（取自最后一帧状态）
+    let game_over_profiles: HashMap<usize, PlayerProfile> = game_state
+        .as_ref()
+        .map(|s| s.snakes.iter().map(|snake| (snake.id, snake.profile.clone())).collect())
+        .unwrap_or_default();
+
     html! {
         <div class="app" onkeydown={handle_keydown} tabindex="0" style="outline: none;">
             <h1>{"多人贪吃蛇游戏"}</h1>
+
+            <ConnectionBanner state={*connection_state} />
+            <NetStatsBar
+                stats={*net_stats}
+                tick_interval_ms={game_state.as_ref().map_or(0, |s| s.tick_interval_ms)}
+            />
             
             if show_matching {
+                <AccountPanel
+                    account={(*account).clone()}
+                    error={(*auth_error).clone()}
+                    on_login={handle_login}
+                    on_register={handle_register}
+                    on_logout={handle_logout}
+                />
+                <ProfileEditor
+                    profile={(*profile).clone()}
+                    error={(*profile_error).clone()}
+                    on_save={handle_save_profile}
+                />
+                <LobbyScreen
+                    rooms={(*rooms).clone()}
+                    current={(*current_room).clone()}
+                    my_id={*my_id}
+                    error={(*room_error).clone()}
+                    on_create={handle_create_room}
+                    on_join={handle_join_room}
+                    on_leave={handle_leave_room}
+                    on_ready={handle_room_ready}
+                    on_refresh={handle_refresh_rooms}
+                />
+                <LeaderboardView board={(*leaderboard).clone()} on_query={handle_leaderboard_query} />
+                <StatsView stats={(*stats).clone()} on_query={handle_stats_query} />
+            }
+
+            if show_matching && current_room.is_none() {
                 <MatchingStatus 
                     current={matching_status.0} 
                     required={matching_status.1} 
                     on_ready={handle_ready}
                     is_ready={*is_ready}
+                    rating={*rating}
                 />
             }
             
-            <GameMap state={(*game_state).clone()} />
+            if show_virtual_keyboard {
+                <KillFeedView entries={kill_feed.borrow().entries()} />
+            }
+            if *use_canvas {
+                <GameCanvas state={(*display_state).clone()} my_id={*my_id} interpolate={*smooth} />
+            } else {
+                <GameMap state={(*display_state).clone()} my_id={*my_id} interpolate={*smooth} />
+            }
+            <label class="smooth-toggle">
+                <input type="checkbox" checked={*smooth} onchange={toggle_smooth} />
+                { "平滑动画" }
+            </label>
+            <label class="smooth-toggle">
+                <input type="checkbox" checked={*use_canvas} onchange={toggle_canvas} />
+                { "Canvas 渲染" }
+            </label>
             
             // 添加虚拟键盘（仅在游戏进行中显示）
             if show_virtual_keyboard {
@@ -159,6 +463,8 @@ fn app() -> Html {
                 <GameOver 
                     rankings={rankings.clone()} 
                     on_restart={handle_restart}
+                    my_id={*my_id}
+                    profiles={game_over_profiles}
                 />
             }
 
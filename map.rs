@@ -0,0 +1,130 @@
+// map.rs
+// 地图定义：宽高、障碍物（墙）与出生点，以及几种内置布局
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use crate::engine::{DEFAULT_MAP_HEIGHT, DEFAULT_MAP_WIDTH};
+use crate::types::{Direction, Position};
+
+/// 出生点：蛇头位置与初始方向，蛇身沿反方向展开
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spawn {
+    pub head: Position,
+    pub direction: Direction,
+}
+
+/// 地图定义
+///
+/// `spawns` 为空时引擎按玩家数把蛇均匀排布在左侧各行（空旷地图的默认方式）；
+/// 否则第 i 个玩家使用 `spawns[i % spawns.len()]`，布局应提供不少于房间人数上限的出生点。
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapDef {
+    pub width: i32,
+    pub height: i32,
+    pub walls: HashSet<Position>,
+    pub spawns: Vec<Spawn>,
+}
+
+impl MapDef {
+    /// 没有障碍物的空旷地图
+    pub fn open(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            walls: HashSet::new(),
+            spawns: vec![],
+        }
+    }
+
+    pub fn is_wall(&self, position: &Position) -> bool {
+        self.walls.contains(position)
+    }
+
+    /// 越界或撞墙
+    pub fn is_blocked(&self, position: &Position) -> bool {
+        !position.is_in_bounds(self.width, self.height) || self.is_wall(position)
+    }
+
+    /// 按行排序的墙格子列表（随 GameState 下发给客户端渲染）
+    pub fn wall_list(&self) -> Vec<Position> {
+        let mut walls: Vec<Position> = self.walls.iter().copied().collect();
+        walls.sort_by_key(|p| (p.y, p.x));
+        walls
+    }
+}
+
+impl Default for MapDef {
+    fn default() -> Self {
+        Self::open(DEFAULT_MAP_WIDTH, DEFAULT_MAP_HEIGHT)
+    }
+}
+
+// ---------------- 内置地图布局 ----------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MapLayout {
+    /// 空旷：没有障碍物
+    #[default]
+    Open,
+    /// 十字：地图中央一个十字形的墙
+    Cross,
+    /// 迷宫：若干交错的横竖墙段
+    Maze,
+}
+
+impl MapLayout {
+    pub const ALL: [MapLayout; 3] = [MapLayout::Open, MapLayout::Cross, MapLayout::Maze];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapLayout::Open => "空旷",
+            MapLayout::Cross => "十字",
+            MapLayout::Maze => "迷宫",
+        }
+    }
+
+    /// 按给定宽高生成地图，墙的位置按比例缩放
+    pub fn build(&self, width: i32, height: i32) -> MapDef {
+        let mut map = MapDef::open(width, height);
+        let (w, h) = (width, height);
+        match self {
+            MapLayout::Open => return map,
+            MapLayout::Cross => {
+                map.walls.extend((w / 4..=w * 3 / 4).map(|x| Position::new(x, h / 2)));
+                map.walls.extend((h / 4..=h * 3 / 4).map(|y| Position::new(w / 2, y)));
+            }
+            MapLayout::Maze => {
+                map.walls.extend((h / 6..=h / 2 - 2).map(|y| Position::new(w / 3, y)));
+                map.walls.extend((h / 2 + 2..=h * 5 / 6).map(|y| Position::new(w * 2 / 3, y)));
+                map.walls.extend((w / 2 + 2..=w * 5 / 6).map(|x| Position::new(x, h / 3)));
+                map.walls.extend((w / 6..=w / 2 - 2).map(|x| Position::new(x, h * 2 / 3)));
+            }
+        }
+        map.spawns = side_spawns(w, h);
+        // 保证出生点所在行从地图边缘到蛇头之间没有墙，蛇身可以完整展开
+        for spawn in &map.spawns {
+            let head = spawn.head;
+            map.walls.retain(|wall| {
+                let behind = match spawn.direction {
+                    Direction::Right => wall.x <= head.x,
+                    _ => wall.x >= head.x,
+                };
+                !(wall.y == head.y && behind)
+            });
+        }
+        map
+    }
+}
+
+/// 左右两侧各 4 个出生点，分布在 1/8、3/8、5/8、7/8 高度；
+/// 按对角交替排列，人数较少时玩家之间距离尽量远
+fn side_spawns(width: i32, height: i32) -> Vec<Spawn> {
+    let row = |k: i32| (2 * k + 1) * height / 8;
+    let left = |k: i32| Spawn {
+        head: Position::new(width / 6, row(k)),
+        direction: Direction::Right,
+    };
+    let right = |k: i32| Spawn {
+        head: Position::new(width - 1 - width / 6, row(k)),
+        direction: Direction::Left,
+    };
+    vec![left(0), right(3), left(3), right(0), left(1), right(2), left(2), right(1)]
+}
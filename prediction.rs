@@ -0,0 +1,83 @@
+// prediction.rs
+// 本地蛇的客户端预测与服务端校正
+use crate::engine::{accepts_turn, next_head, select_turn};
+use crate::types::{GameState, Direction};
+
+/// 客户端预测器
+///
+/// 每个输入分配递增的序号，服务端在 `Snake.last_input_seq` 中回传已处理的最大序号。
+/// 未确认的输入即仍在服务端输入队列中（或在路上）的转向，本地按与服务端相同的入队规则缓存，
+/// 无效的输入（反向、重复、队列已满）直接丢弃而不发送。
+/// 仍未确认的输入会立即作用在最近的权威状态上：本地蛇按队首的输入转向并提前走一步
+/// （与服务端下一 tick 使用相同的 `select_turn` / `next_head` 规则），
+/// 收到新的权威状态后丢弃已确认的输入并重新预测，预测错误时自然回到服务端结果。
+#[derive(Debug, Clone, Default)]
+pub struct Predictor {
+    player_id: Option<usize>,
+    next_seq: u32,
+    // 已发送但服务端尚未确认的输入 (序号, 方向)
+    pending: Vec<(u32, Direction)>,
+    // 最近的权威状态
+    server: Option<GameState>,
+}
+
+impl Predictor {
+    pub fn set_player(&mut self, player_id: usize) {
+        self.player_id = Some(player_id);
+    }
+
+    /// 记录一次本地输入，返回随 `SequencedInput` 发送的序号；
+    /// 按 `accepts_turn` 被丢弃时返回 None，不需要发送
+    pub fn input(&mut self, direction: Direction) -> Option<u32> {
+        let state = self.server.as_ref();
+        let snake = state.and_then(|s| s.snakes.iter().find(|snake| Some(snake.id) == self.player_id));
+        if let (Some(state), Some(snake)) = (state, snake) {
+            let queued: Vec<Direction> = self.pending.iter().map(|(_, dir)| *dir).collect();
+            if !accepts_turn(snake, &queued, direction, state.input_queue_depth) {
+                return None;
+            }
+        }
+        self.next_seq += 1;
+        self.pending.push((self.next_seq, direction));
+        Some(self.next_seq)
+    }
+
+    /// 收到权威状态：丢弃已确认的输入；对局未进行时清空全部待确认输入
+    pub fn on_server_state(&mut self, state: GameState) {
+        let running = state.game_started && !state.game_over;
+        let acked = self
+            .player_id
+            .and_then(|id| state.snakes.iter().find(|s| s.id == id))
+            .filter(|s| s.alive)
+            .map(|s| s.last_input_seq);
+        match acked {
+            Some(acked) if running => self.pending.retain(|(seq, _)| *seq > acked),
+            _ => self.pending.clear(),
+        }
+        self.server = Some(state);
+    }
+
+    /// 用于渲染的状态：权威状态 + 本地蛇对未确认输入的预测
+    pub fn predicted(&self) -> Option<GameState> {
+        let mut state = self.server.clone()?;
+        if self.pending.is_empty() {
+            return Some(state);
+        }
+        let Some(id) = self.player_id else {
+            return Some(state);
+        };
+
+        let foods: Vec<_> = state.foods.iter().map(|f| f.position).collect();
+        let queued: Vec<Direction> = self.pending.iter().map(|(_, dir)| *dir).collect();
+        let (width, height, wrap) = (state.map_width, state.map_height, state.wrap);
+        if let Some(snake) = state.snakes.iter_mut().find(|s| s.id == id && s.alive) {
+            if let Some(direction) = select_turn(snake, &queued) {
+                snake.turn(direction);
+            }
+            if let Some(head) = next_head(snake, width, height, wrap) {
+                snake.advance_to(head, foods.contains(&head));
+            }
+        }
+        Some(state)
+    }
+}
@@ -0,0 +1,77 @@
+// profile.rs
+// 玩家资料（昵称、颜色、头像）的可选项与校验规则，服务端据此校验，前端据此提供选项
+use crate::types::PlayerProfile;
+
+/// 昵称最大长度（字符）
+pub const MAX_NAME_LEN: usize = 12;
+/// 可选的蛇颜色（同时是未设置颜色时按蛇ID分配的调色板）
+pub const COLORS: [&str; 10] = [
+    "#4CAF50", "#2196F3", "#FFC107", "#9C27B0", "#FF9800",
+    "#00BCD4", "#8BC34A", "#FF5722", "#607D8B", "#795548",
+];
+/// 可选的头像
+pub const AVATARS: [&str; 10] = ["🐍", "🐉", "🦎", "🐢", "🐸", "🦊", "🐱", "🐶", "🐼", "🐧"];
+// 昵称中不允许出现的词（匹配前去掉空白与常见分隔符并转为小写）
+const BLOCKED_WORDS: &[&str] = &[
+    "fuck", "shit", "bitch", "cunt", "asshole", "nigger", "faggot", "retard",
+    "傻逼", "煞笔", "操你", "草泥马", "妈的", "他妈", "狗日", "贱人", "婊子", "滚蛋",
+];
+
+impl PlayerProfile {
+    /// 显示名：未设置昵称时为"蛇{id}"
+    pub fn display_name(&self, id: usize) -> String {
+        if self.name.is_empty() {
+            format!("蛇{}", id)
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// 带头像的显示名，用于地图标签和排名
+    pub fn label(&self, id: usize) -> String {
+        match &self.avatar {
+            Some(avatar) => format!("{} {}", avatar, self.display_name(id)),
+            None => self.display_name(id),
+        }
+    }
+}
+
+/// 规范化：去掉首尾空白并把连续空白合并为一个空格
+pub fn normalize(mut profile: PlayerProfile) -> PlayerProfile {
+    profile.name = profile.name.split_whitespace().collect::<Vec<_>>().join(" ");
+    profile
+}
+
+/// 校验玩家资料（应先规范化），返回第一个不合法项的说明
+pub fn validate(profile: &PlayerProfile) -> Result<(), String> {
+    if profile.name.chars().count() > MAX_NAME_LEN {
+        return Err(format!("昵称不能超过 {} 个字符", MAX_NAME_LEN));
+    }
+    if profile.name.chars().any(char::is_control) {
+        return Err("昵称包含非法字符".to_string());
+    }
+    if is_blocked(&profile.name) {
+        return Err("昵称包含不文明用语".to_string());
+    }
+    if let Some(color) = &profile.color {
+        if !COLORS.iter().any(|c| c.eq_ignore_ascii_case(color)) {
+            return Err("不支持的颜色".to_string());
+        }
+    }
+    if let Some(avatar) = &profile.avatar {
+        if !AVATARS.contains(&avatar.as_str()) {
+            return Err("不支持的头像".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// 是否包含屏蔽词（忽略大小写、空白和常见分隔符，避免用"f u-c_k"绕过）
+fn is_blocked(name: &str) -> bool {
+    let compact: String = name
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    BLOCKED_WORDS.iter().any(|word| compact.contains(word))
+}
@@ -0,0 +1,75 @@
+// settings.rs
+// 房间设置的默认值与取值范围，服务端据此校验，前端据此限制输入
+use std::ops::RangeInclusive;
+use crate::engine::EngineConfig;
+use crate::types::{RoomSettings, WinCondition};
+
+/// 房间人数范围（内置地图提供 8 个出生点）
+pub const PLAYERS: RangeInclusive<usize> = 1..=8;
+/// tick 间隔范围（毫秒），即 2 ~ 20 tick/s
+pub const TICK_INTERVAL_MS: RangeInclusive<u32> = 50..=500;
+/// 地图宽高范围（格）
+pub const MAP_SIZE: RangeInclusive<i32> = 15..=80;
+pub const START_LENGTH: RangeInclusive<usize> = 1..=10;
+pub const FOOD_COUNT: RangeInclusive<usize> = 1..=20;
+/// 输入队列长度范围（步）
+pub const INPUT_QUEUE_DEPTH: RangeInclusive<usize> = 1..=5;
+pub const TARGET_SCORE: RangeInclusive<u32> = 10..=1000;
+/// 限时范围（秒）
+pub const TIME_LIMIT_SECS: RangeInclusive<u32> = 30..=600;
+/// 房间名最大长度（字符）
+pub const MAX_NAME_LEN: usize = 20;
+
+impl Default for RoomSettings {
+    fn default() -> Self {
+        let engine = EngineConfig::default();
+        Self {
+            name: String::new(),
+            layout: Default::default(),
+            wrap: engine.wrap,
+            min_players: 2,
+            max_players: *PLAYERS.end(),
+            tick_interval_ms: engine.tick_interval_ms,
+            map_width: engine.map.width,
+            map_height: engine.map.height,
+            start_length: engine.start_length,
+            food_count: engine.food_count,
+            win_condition: WinCondition::LastAlive,
+            collision_rule: engine.collision_rule,
+            input_queue_depth: engine.input_queue_depth,
+        }
+    }
+}
+
+/// 校验房间设置，返回第一个不合法项的说明
+pub fn validate(settings: &RoomSettings) -> Result<(), String> {
+    check("最少人数", settings.min_players, &PLAYERS)?;
+    check("人数上限", settings.max_players, &PLAYERS)?;
+    if settings.min_players > settings.max_players {
+        return Err("最少人数不能超过人数上限".to_string());
+    }
+    check("tick 间隔（毫秒）", settings.tick_interval_ms, &TICK_INTERVAL_MS)?;
+    check("地图宽度", settings.map_width, &MAP_SIZE)?;
+    check("地图高度", settings.map_height, &MAP_SIZE)?;
+    check("初始长度", settings.start_length, &START_LENGTH)?;
+    // 出生点位于距边缘 1/6 地图宽度处，蛇身需要在地图内完整展开
+    let max_length = (settings.map_width / 6 + 1) as usize;
+    if settings.start_length > max_length {
+        return Err(format!("初始长度不能超过 {}（地图宽度的 1/6 加 1）", max_length));
+    }
+    check("食物数量", settings.food_count, &FOOD_COUNT)?;
+    check("输入缓冲", settings.input_queue_depth, &INPUT_QUEUE_DEPTH)?;
+    match settings.win_condition {
+        WinCondition::LastAlive => Ok(()),
+        WinCondition::TargetScore(score) => check("目标分数", score, &TARGET_SCORE),
+        WinCondition::TimeLimit(secs) => check("限时（秒）", secs, &TIME_LIMIT_SECS),
+    }
+}
+
+fn check<T: PartialOrd + std::fmt::Display>(label: &str, value: T, range: &RangeInclusive<T>) -> Result<(), String> {
+    if range.contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("{}须在 {} ~ {} 之间", label, range.start(), range.end()))
+    }
+}
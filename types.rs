@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use crate::map::MapLayout;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub enum Direction {
@@ -21,6 +22,51 @@ pub struct Snake {
     pub direction: Direction,
     pub alive: bool,
     pub score: u32,
+    // 服务端已处理的该玩家最大输入序号（用于客户端预测校正）
+    #[serde(default)]
+    pub last_input_seq: u32,
+    // 玩家资料（开局时由服务端填入）
+    #[serde(default)]
+    pub profile: PlayerProfile,
+    // 死因，存活时为 None
+    #[serde(default)]
+    pub death: Option<DeathCause>,
+}
+
+/// 蛇的死因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeathCause {
+    /// 越界或撞到障碍物
+    Wall,
+    /// 撞到自己
+    OwnBody,
+    /// 撞到其他蛇（含迎头相撞）
+    OtherSnake,
+    /// 对局中离开房间
+    Left,
+    /// 断线后未在宽限期内恢复
+    Disconnected,
+}
+
+/// 引擎在一个 tick 内产生的事件（随状态下发，用于击杀播报等）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum GameEvent {
+    /// 吃到食物
+    AteFood { id: usize, position: Position },
+    /// 死亡；撞到其他蛇时 `killer` 为被撞的蛇（迎头相撞时双方互为击杀者）
+    Died { id: usize, cause: DeathCause, killer: Option<usize> },
+}
+
+/// 玩家资料：昵称、颜色与头像，显示在地图和排名中（取值范围见 `profile` 模块）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct PlayerProfile {
+    // 为空时显示为"蛇{id}"
+    pub name: String,
+    // 十六进制颜色，为 None 时按蛇ID分配
+    pub color: Option<String>,
+    pub avatar: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -36,6 +82,209 @@ pub enum GameMessage {
     GameState(GameState),
     MatchingStatus { current: usize, required: usize },
     GameOver { rankings: Vec<(usize, u32)> },
+    // 服务端下发的会话令牌（连接建立或会话恢复成功时发送）
+    Session { token: String },
+    // 客户端重连后携带之前的令牌，请求找回原来的蛇
+    Resume { token: String },
+    // 服务端告知客户端自己对应的蛇ID（即 Snake.id）与所在房间
+    Welcome { player_id: usize, room_id: String },
+    // 增量状态：只包含相对 `base_tick` 状态的变化
+    StateDelta(StateDelta),
+    // 带序号的玩家输入，服务端在 Snake.last_input_seq 中回传已处理的序号
+    SequencedInput { seq: u32, direction: Direction },
+    // 客户端检测到增量缺失时请求完整快照
+    RequestSnapshot,
+    // 测量往返延迟：客户端发送本地时间（毫秒），服务端原样带回并附上服务端时间
+    Ping { client_time: f64 },
+    Pong { client_time: f64, server_time_ms: u64 },
+    // 房间：列出公开房间、创建房间、凭房间码加入、离开当前房间
+    ListRooms,
+    RoomList { rooms: Vec<RoomInfo> },
+    CreateRoom { settings: RoomSettings },
+    JoinRoom { code: String },
+    LeaveRoom,
+    // 所在房间的成员或准备状态变化时推送给房间内所有成员
+    RoomUpdate(RoomDetails),
+    // 已离开房间（主动离开或被移出）
+    RoomLeft,
+    // 创建/加入房间失败的原因（房间不存在、已满、对局进行中等）
+    RoomError { message: String },
+    // 客户端持久化的玩家标识（连接建立后发送），服务端据此关联积分
+    Identify { key: String },
+    // 玩家当前积分与已计分的对局数（标识后及每局计分后推送）
+    Rating { rating: u32, games: u32 },
+    // 设置玩家资料（连接建立后及修改时发送），下一局开始生效
+    SetProfile { profile: PlayerProfile },
+    // 服务端接受的玩家资料（规范化后）
+    Profile { profile: PlayerProfile },
+    // 玩家资料校验失败的原因
+    ProfileError { message: String },
+    // 账号：注册、登录、凭登录令牌自动登录（连接建立后发送）、退出登录
+    Register { username: String, password: String },
+    Login { username: String, password: String },
+    Authenticate { token: String },
+    Logout,
+    // 登录成功：用户名与登录令牌（客户端保存后用于自动登录）
+    LoggedIn { username: String, token: String },
+    // 已退出登录（主动退出或登录令牌失效）
+    LoggedOut,
+    // 注册 / 登录失败的原因
+    AuthError { message: String },
+    // 查询排行榜（`mode` 为 None 时包含所有玩法）
+    GetLeaderboard { period: LeaderboardPeriod, mode: Option<GameMode> },
+    Leaderboard(Leaderboard),
+    // 查询自己的战绩与最近的对局记录（按积分使用的玩家标识统计）
+    GetStats,
+    PlayerStats(PlayerStats),
+}
+
+/// 房间设置（创建房间时指定，取值范围见 `settings.rs`）；缺省字段使用默认值
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RoomSettings {
+    pub name: String,
+    pub layout: MapLayout,
+    pub wrap: bool,
+    // 开局所需最少人数与房间人数上限
+    pub min_players: usize,
+    pub max_players: usize,
+    pub tick_interval_ms: u32,
+    pub map_width: i32,
+    pub map_height: i32,
+    pub start_length: usize,
+    pub food_count: usize,
+    pub win_condition: WinCondition,
+    pub collision_rule: CollisionRule,
+    // 每个玩家最多排队的转向数
+    pub input_queue_depth: usize,
+}
+
+/// 迎头相撞（蛇头落在同一格，或相向穿过）的结算规则，完整的碰撞判定顺序见 `engine.rs`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum CollisionRule {
+    /// 双方都死亡
+    #[default]
+    BothDie,
+    /// 较长的一方存活，一样长时都死亡
+    LongerWins,
+    /// 双方退回移动前的位置并掉头
+    Bounce,
+}
+
+/// 胜利条件：无论哪种条件，存活的蛇不足时对局都会结束
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum WinCondition {
+    /// 最后存活（单人时直到死亡）
+    #[default]
+    LastAlive,
+    /// 任意一条蛇达到目标分数
+    TargetScore(u32),
+    /// 限时（秒），时间到时按分数排名
+    TimeLimit(u32),
+}
+
+/// 玩法（按胜利条件区分），排行榜可按玩法筛选
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    LastAlive,
+    TargetScore,
+    TimeLimit,
+}
+
+/// 排行榜统计周期（按 UTC 计算）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum LeaderboardPeriod {
+    #[default]
+    AllTime,
+    /// 本赛季（自然季度）
+    Season,
+    /// 本周（周一起）
+    Weekly,
+}
+
+/// 排行榜中的一名玩家，按胜场、最高分、总分排序
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    // 最近一局使用的名字
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    pub best_score: u32,
+    pub total_score: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Leaderboard {
+    pub period: LeaderboardPeriod,
+    pub mode: Option<GameMode>,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// 玩家战绩汇总与最近的对局
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct PlayerStats {
+    pub games: u32,
+    // 多人对局场次与其中的胜场（胜率 = 胜场 / 多人对局场次）
+    pub multiplayer_games: u32,
+    pub wins: u32,
+    pub total_score: u64,
+    pub best_score: u32,
+    // 历史最长蛇身
+    pub longest: u32,
+    // 最近的对局，新的在前
+    pub recent: Vec<MatchSummary>,
+}
+
+/// 一局对局的记录
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MatchSummary {
+    pub ended_at_ms: u64,
+    pub duration_ms: u64,
+    pub mode: GameMode,
+    // 参与对局的蛇数，包括没有玩家标识、未出现在 participants 中的玩家
+    pub players: u32,
+    // 查询者本人
+    pub me: MatchParticipant,
+    // 有玩家标识的参与者（含本人），按名次排列
+    pub participants: Vec<MatchParticipant>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MatchParticipant {
+    pub name: String,
+    pub score: u32,
+    // 名次，从 0 开始，并列时相同
+    pub place: u32,
+    pub max_length: u32,
+    // 存活到最后时为 None
+    pub death: Option<DeathCause>,
+}
+
+/// 房间列表中的一项
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoomInfo {
+    pub code: String,
+    pub name: String,
+    pub players: usize,
+    pub max_players: usize,
+    pub in_game: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoomMember {
+    pub id: usize,
+    pub ready: bool,
+    #[serde(default)]
+    pub profile: PlayerProfile,
+}
+
+/// 当前所在房间的完整信息
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoomDetails {
+    pub info: RoomInfo,
+    pub settings: RoomSettings,
+    pub members: Vec<RoomMember>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -45,6 +294,143 @@ pub struct GameState {
     pub foods: Vec<Food>,
     pub game_started: bool,
     pub game_over: bool,
+    // 服务端 tick 序号，每推进一步加 1
+    #[serde(default)]
+    pub tick: u64,
+    // 生成该状态时的服务端时间（Unix 毫秒）
+    #[serde(default)]
+    pub server_time_ms: u64,
+    // 服务端 tick 间隔（毫秒），即 tick 速率
+    #[serde(default)]
+    pub tick_interval_ms: u32,
+    // 地图宽高（格），可以不是正方形
+    #[serde(default = "default_map_width")]
+    pub map_width: i32,
+    #[serde(default = "default_map_height")]
+    pub map_height: i32,
+    // 地图中的障碍物格子，整局不变
+    #[serde(default)]
+    pub walls: Vec<Position>,
+    // 环形地图：越过边缘从对侧出现，而不是撞墙死亡
+    #[serde(default)]
+    pub wrap: bool,
+    // 最近一个 tick 产生的事件
+    #[serde(default)]
+    pub events: Vec<GameEvent>,
+    // 每个玩家最多排队的转向数（客户端按同样的规则缓存输入）
+    #[serde(default = "default_input_queue_depth")]
+    pub input_queue_depth: usize,
+}
+
+fn default_map_width() -> i32 {
+    crate::engine::DEFAULT_MAP_WIDTH
+}
+
+fn default_map_height() -> i32 {
+    crate::engine::DEFAULT_MAP_HEIGHT
+}
+
+fn default_input_queue_depth() -> usize {
+    crate::engine::EngineConfig::default().input_queue_depth
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StateDelta {
+    pub base_tick: u64,
+    pub tick: u64,
+    pub server_time_ms: u64,
+    pub snakes: Vec<SnakeDelta>,
+    pub foods_eaten: Vec<Position>,
+    pub foods_spawned: Vec<Position>,
+    pub game_over: bool,
+    // `tick` 产生的事件
+    #[serde(default)]
+    pub events: Vec<GameEvent>,
+}
+
+// 单条蛇的变化：先弹出 `tail_pops` 节蛇尾，再依次把 `head_pushes` 插到蛇头
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SnakeDelta {
+    pub id: usize,
+    pub head_pushes: Vec<Position>,
+    pub tail_pops: usize,
+    pub direction: Direction,
+    pub alive: bool,
+    pub score: u32,
+    pub last_input_seq: u32,
+    #[serde(default)]
+    pub death: Option<DeathCause>,
+}
+
+impl WinCondition {
+    pub fn mode(&self) -> GameMode {
+        match self {
+            WinCondition::LastAlive => GameMode::LastAlive,
+            WinCondition::TargetScore(_) => GameMode::TargetScore,
+            WinCondition::TimeLimit(_) => GameMode::TimeLimit,
+        }
+    }
+}
+
+impl DeathCause {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeathCause::Wall => "撞墙",
+            DeathCause::OwnBody => "撞到自己",
+            DeathCause::OtherSnake => "撞到其他蛇",
+            DeathCause::Left => "中途离开",
+            DeathCause::Disconnected => "掉线",
+        }
+    }
+}
+
+impl CollisionRule {
+    pub const ALL: [CollisionRule; 3] = [CollisionRule::BothDie, CollisionRule::LongerWins, CollisionRule::Bounce];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CollisionRule::BothDie => "双方死亡",
+            CollisionRule::LongerWins => "较长者存活",
+            CollisionRule::Bounce => "弹回",
+        }
+    }
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::LastAlive, GameMode::TargetScore, GameMode::TimeLimit];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::LastAlive => "最后存活",
+            GameMode::TargetScore => "目标分数",
+            GameMode::TimeLimit => "限时",
+        }
+    }
+}
+
+impl LeaderboardPeriod {
+    pub const ALL: [LeaderboardPeriod; 3] =
+        [LeaderboardPeriod::AllTime, LeaderboardPeriod::Season, LeaderboardPeriod::Weekly];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LeaderboardPeriod::AllTime => "总榜",
+            LeaderboardPeriod::Season => "赛季榜",
+            LeaderboardPeriod::Weekly => "周榜",
+        }
+    }
+}
+
+impl Direction {
+    /// 反方向（用于禁止原地掉头）
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
 }
 
 impl Position {
@@ -52,8 +438,35 @@ impl Position {
         Self { x, y }
     }
 
-    pub fn is_in_bounds(&self, map_size: i32) -> bool {
-        self.x >= 0 && self.x < map_size && self.y >= 0 && self.y < map_size
+    /// 沿指定方向移动一格后的位置（y 轴向下为正，与前端渲染一致）
+    pub fn moved(&self, direction: Direction) -> Self {
+        match direction {
+            Direction::Up => Self::new(self.x, self.y - 1),
+            Direction::Down => Self::new(self.x, self.y + 1),
+            Direction::Left => Self::new(self.x - 1, self.y),
+            Direction::Right => Self::new(self.x + 1, self.y),
+        }
+    }
+
+    pub fn is_in_bounds(&self, width: i32, height: i32) -> bool {
+        self.x >= 0 && self.x < width && self.y >= 0 && self.y < height
+    }
+
+    /// 环形地图：越过边缘的坐标回到对侧
+    pub fn wrapped(&self, width: i32, height: i32) -> Self {
+        Self::new(self.x.rem_euclid(width), self.y.rem_euclid(height))
+    }
+
+    /// 到相邻格 `to` 的方向，`width`、`height` 为地图宽高：环形地图上隔着边缘的两格
+    /// （如 x 为 0 与 width - 1）同样视为相邻；不相邻时为 None
+    pub fn direction_to(&self, to: &Position, width: i32, height: i32) -> Option<Direction> {
+        match (to.x - self.x, to.y - self.y) {
+            (dx, 0) if dx == 1 || dx == 1 - width => Some(Direction::Right),
+            (dx, 0) if dx == -1 || dx == width - 1 => Some(Direction::Left),
+            (0, dy) if dy == 1 || dy == 1 - height => Some(Direction::Down),
+            (0, dy) if dy == -1 || dy == height - 1 => Some(Direction::Up),
+            _ => None,
+        }
     }
 }
 
@@ -62,6 +475,45 @@ impl Snake {
         self.body.first()
     }
 
+    /// 按当前方向计算下一步的蛇头位置
+    pub fn next_head(&self) -> Option<Position> {
+        self.head().map(|head| head.moved(self.direction))
+    }
+
+    /// 转向：长度大于 1 时忽略与当前方向相反的输入，返回是否生效
+    pub fn turn(&mut self, direction: Direction) -> bool {
+        if self.body.len() > 1 && direction == self.direction.opposite() {
+            return false;
+        }
+        self.direction = direction;
+        true
+    }
+
+    /// 前进一格：新蛇头入队，`grow` 为 false 时同时弹出蛇尾
+    pub fn advance(&mut self, grow: bool) {
+        if let Some(next) = self.next_head() {
+            self.advance_to(next, grow);
+        }
+    }
+
+    /// 前进到指定位置（环形地图上由调用方先把蛇头折回地图内）
+    pub fn advance_to(&mut self, next: Position, grow: bool) {
+        self.body.insert(0, next);
+        if !grow {
+            self.body.pop();
+        }
+    }
+
+    /// 掉头：蛇头与蛇尾对调，沿原蛇尾的延伸方向前进（长度为 1 时直接反向）；
+    /// `width`、`height` 为地图宽高，用于识别环形地图上跨越边缘的蛇尾
+    pub fn reverse(&mut self, width: i32, height: i32) {
+        self.body.reverse();
+        self.direction = match self.body.as_slice() {
+            [head, neck, ..] => neck.direction_to(head, width, height).unwrap_or(self.direction.opposite()),
+            _ => self.direction.opposite(),
+        };
+    }
+
     pub fn hits_self(&self) -> bool {
         if let Some(head) = self.head() {
             self.body[1..].contains(head)
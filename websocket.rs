@@ -1,67 +1,265 @@
 // src/websocket.rs
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use yew::prelude::*;
-use web_sys::{WebSocket, MessageEvent, CloseEvent, console};
+use web_sys::{WebSocket, MessageEvent, CloseEvent, console, window};
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
-use crate::types::{GameState, GameMessage};
+use crate::types::{GameState, GameMessage, StateDelta, RoomInfo, RoomDetails, PlayerProfile, Leaderboard, PlayerStats};
+use crate::codec::{self, Codec, Frame};
+use crate::config;
+use crate::delta;
 
-#[derive(Debug, Clone)]
-pub struct WsClient {
-    ws: Option<WebSocket>,
+// 重连退避参数（毫秒）
+const RECONNECT_BASE_DELAY_MS: u32 = 500;
+const RECONNECT_MAX_DELAY_MS: u32 = 10_000;
+// 连续重连失败达到该次数后放弃
+const MAX_RECONNECT_ATTEMPTS: u32 = 10;
+// Ping 间隔（毫秒）
+const PING_INTERVAL_MS: i32 = 2_000;
+// 延迟与抖动的平滑系数（与 RFC 3550 的抖动估计一致）
+const STATS_SMOOTHING: f64 = 1.0 / 16.0;
+
+/// 连接状态（通过 `on_connection_state` 通知界面）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connecting,
+    Open,
+    Reconnecting { attempt: u32, delay_ms: u32 },
+    Closed,
+}
+
+/// 网络统计（通过 `on_net_stats` 定期上报）
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NetStats {
+    // 平滑后的往返延迟（毫秒），收到第一个 Pong 前为 None
+    pub rtt_ms: Option<f64>,
+    // 状态到达间隔相对服务端 tick 间隔的抖动（毫秒）
+    pub jitter_ms: f64,
+    // 因 tick 不连续而跳过的 tick 数
+    pub dropped_ticks: u64,
+    // 因过期（乱序）被丢弃的状态帧数
+    pub stale_frames: u64,
+}
+
+// 统计所需的上一帧到达信息
+#[derive(Debug, Default)]
+struct NetTracker {
+    stats: NetStats,
+    // (tick, 服务端时间, 本地到达时间)
+    last_arrival: Option<(u64, u64, f64)>,
+}
+
+impl NetTracker {
+    // 记录一帧新状态的到达，更新抖动与丢帧计数
+    fn record_state(&mut self, tick: u64, server_time_ms: u64, now: f64) {
+        if let Some((last_tick, last_server, last_now)) = self.last_arrival {
+            if tick > last_tick {
+                self.stats.dropped_ticks += tick - last_tick - 1;
+                let transit = (now - last_now) - (server_time_ms as f64 - last_server as f64);
+                self.stats.jitter_ms += (transit.abs() - self.stats.jitter_ms) * STATS_SMOOTHING;
+            }
+        }
+        self.last_arrival = Some((tick, server_time_ms, now));
+    }
+
+    fn record_pong(&mut self, client_time: f64, now: f64) {
+        let sample = (now - client_time).max(0.0);
+        self.stats.rtt_ms = Some(match self.stats.rtt_ms {
+            Some(rtt) => rtt + (sample - rtt) * STATS_SMOOTHING * 2.0,
+            None => sample,
+        });
+    }
+}
+
+// 回调集合：每次（重）连都会重新挂到新的 WebSocket 上
+#[derive(Debug, Clone, Default)]
+struct Handlers {
     on_game_state: Option<Callback<GameState>>,
     on_matching_status: Option<Callback<(usize, usize)>>,
     on_game_over: Option<Callback<Vec<(usize, u32)>>>,
+    on_welcome: Option<Callback<(usize, String)>>,
+    on_net_stats: Option<Callback<NetStats>>,
+    on_connection_state: Option<Callback<ConnectionState>>,
+    on_room_list: Option<Callback<Vec<RoomInfo>>>,
+    // 离开房间时为 None
+    on_room_update: Option<Callback<Option<RoomDetails>>>,
+    on_room_error: Option<Callback<String>>,
+    // (积分, 已计分对局数)
+    on_rating: Option<Callback<(u32, u32)>>,
+    // 服务端接受的玩家资料 / 资料校验失败的原因
+    on_profile: Option<Callback<PlayerProfile>>,
+    on_profile_error: Option<Callback<String>>,
+    // 已登录的用户名，退出登录时为 None
+    on_account: Option<Callback<Option<String>>>,
+    on_auth_error: Option<Callback<String>>,
+    on_leaderboard: Option<Callback<Leaderboard>>,
+    on_stats: Option<Callback<PlayerStats>>,
+}
+
+// 当前连接及其事件闭包，替换连接时旧闭包随之释放
+#[derive(Debug)]
+struct Connection {
+    ws: WebSocket,
+    _on_open: Closure<dyn FnMut()>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_close: Closure<dyn FnMut(CloseEvent)>,
+}
+
+// 定时 Ping 的 interval ID 及其闭包
+#[derive(Debug)]
+struct PingTimer {
+    id: i32,
+    _on_tick: Closure<dyn FnMut()>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WsClient {
+    url: String,
+    handlers: Handlers,
+    connection: Rc<RefCell<Option<Connection>>>,
+    // 当前连续重连次数，连接成功后清零
+    attempt: Rc<Cell<u32>>,
+    // 主动关闭或放弃重连后不再重连
+    closed: Rc<Cell<bool>>,
+    // 服务端下发的会话令牌，重连后用于找回原来的蛇
+    session_token: Rc<RefCell<Option<String>>>,
+    // 握手时协商出的发送编码（接收端按帧类型自动识别）
+    codec: Rc<Cell<Codec>>,
+    // 最近的游戏状态（完整快照或应用增量后的结果），作为应用增量的基准
+    last_state: Rc<RefCell<Option<GameState>>>,
+    // 已请求完整快照、尚未收到时不重复请求
+    awaiting_snapshot: Rc<Cell<bool>>,
+    net: Rc<RefCell<NetTracker>>,
+    ping_timer: Rc<RefCell<Option<PingTimer>>>,
 }
 
 impl WsClient {
-    // 带 URL 参数的构造函数
+    // 带 URL 参数的构造函数（调用 start_listening 后才真正建立连接）
     pub fn new(url: &str) -> Self {
-        let ws = WebSocket::new(url)
-            .expect("Failed to create WebSocket connection. Check URL or network.");
-        ws.set_binary_type(web_sys::BinaryType::Arraybuffer);
-        
-        // 监听连接成功事件
-        let open_closure = Closure::wrap(Box::new(|| {
-            console::log_1(&"WebSocket connection established successfully!".into());
-        }) as Box<dyn FnMut()>);
-        ws.set_onopen(Some(open_closure.as_ref().unchecked_ref()));
-        open_closure.forget();
-
         Self {
-            ws: Some(ws),
-            on_game_state: None,
-            on_matching_status: None,
-            on_game_over: None,
+            url: url.to_string(),
+            handlers: Handlers::default(),
+            connection: Rc::new(RefCell::new(None)),
+            attempt: Rc::new(Cell::new(0)),
+            closed: Rc::new(Cell::new(false)),
+            session_token: Rc::new(RefCell::new(None)),
+            codec: Rc::new(Cell::new(Codec::Json)),
+            last_state: Rc::new(RefCell::new(None)),
+            awaiting_snapshot: Rc::new(Cell::new(false)),
+            net: Rc::new(RefCell::new(NetTracker::default())),
+            ping_timer: Rc::new(RefCell::new(None)),
         }
     }
 
     // 注册游戏状态回调
     pub fn on_game_state(mut self, callback: Callback<GameState>) -> Self {
-        self.on_game_state = Some(callback);
+        self.handlers.on_game_state = Some(callback);
         self
     }
 
     // 注册匹配状态回调
     pub fn on_matching_status(mut self, callback: Callback<(usize, usize)>) -> Self {
-        self.on_matching_status = Some(callback);
+        self.handlers.on_matching_status = Some(callback);
         self
     }
 
     // 注册游戏结束回调
     pub fn on_game_over(mut self, callback: Callback<Vec<(usize, u32)>>) -> Self {
-        self.on_game_over = Some(callback);
+        self.handlers.on_game_over = Some(callback);
+        self
+    }
+
+    // 注册身份分配回调（自己的蛇ID、房间ID）
+    pub fn on_welcome(mut self, callback: Callback<(usize, String)>) -> Self {
+        self.handlers.on_welcome = Some(callback);
+        self
+    }
+
+    // 注册网络统计回调（延迟、抖动、丢帧），每次收到 Pong 时上报
+    pub fn on_net_stats(mut self, callback: Callback<NetStats>) -> Self {
+        self.handlers.on_net_stats = Some(callback);
+        self
+    }
+
+    // 注册连接状态回调（连接中 / 已连接 / 重连中 / 已关闭）
+    pub fn on_connection_state(mut self, callback: Callback<ConnectionState>) -> Self {
+        self.handlers.on_connection_state = Some(callback);
+        self
+    }
+
+    // 注册房间列表回调
+    pub fn on_room_list(mut self, callback: Callback<Vec<RoomInfo>>) -> Self {
+        self.handlers.on_room_list = Some(callback);
+        self
+    }
+
+    // 注册所在房间变化回调（离开房间时收到 None）
+    pub fn on_room_update(mut self, callback: Callback<Option<RoomDetails>>) -> Self {
+        self.handlers.on_room_update = Some(callback);
+        self
+    }
+
+    // 注册房间操作失败回调
+    pub fn on_room_error(mut self, callback: Callback<String>) -> Self {
+        self.handlers.on_room_error = Some(callback);
+        self
+    }
+
+    // 注册积分回调：(积分, 已计分对局数)
+    pub fn on_rating(mut self, callback: Callback<(u32, u32)>) -> Self {
+        self.handlers.on_rating = Some(callback);
+        self
+    }
+
+    // 注册玩家资料被接受的回调
+    pub fn on_profile(mut self, callback: Callback<PlayerProfile>) -> Self {
+        self.handlers.on_profile = Some(callback);
+        self
+    }
+
+    // 注册玩家资料校验失败的回调
+    pub fn on_profile_error(mut self, callback: Callback<String>) -> Self {
+        self.handlers.on_profile_error = Some(callback);
+        self
+    }
+
+    // 注册登录状态回调（登录成功为用户名，退出登录为 None）
+    pub fn on_account(mut self, callback: Callback<Option<String>>) -> Self {
+        self.handlers.on_account = Some(callback);
+        self
+    }
+
+    // 注册注册 / 登录失败回调
+    pub fn on_auth_error(mut self, callback: Callback<String>) -> Self {
+        self.handlers.on_auth_error = Some(callback);
+        self
+    }
+
+    // 注册排行榜查询结果回调
+    pub fn on_leaderboard(mut self, callback: Callback<Leaderboard>) -> Self {
+        self.handlers.on_leaderboard = Some(callback);
+        self
+    }
+
+    // 注册个人战绩查询结果回调
+    pub fn on_stats(mut self, callback: Callback<PlayerStats>) -> Self {
+        self.handlers.on_stats = Some(callback);
         self
     }
 
     // 发送消息到后端
     pub fn send(&self, msg: GameMessage) {
-        if let Some(ws) = &self.ws {
-            match serde_json::to_string(&msg) {
-                Ok(msg_str) => {
-                    if ws.send_with_str(&msg_str).is_err() {
+        if let Some(conn) = self.connection.borrow().as_ref() {
+            match self.codec.get().encode(&msg) {
+                Ok(frame) => {
+                    let sent = match &frame {
+                        Frame::Text(text) => conn.ws.send_with_str(text),
+                        Frame::Binary(bytes) => conn.ws.send_with_u8_array(bytes),
+                    };
+                    // 不逐条记录收发的消息：其中包含登录密码与会话令牌
+                    if sent.is_err() {
                         console::error_1(&"Failed to send WebSocket message: connection closed.".into());
-                    } else {
-                        console::log_1(&format!("WS sent: {}", msg_str).into());
                     }
                 }
                 Err(e) => {
@@ -73,71 +271,377 @@ impl WsClient {
         }
     }
 
-    // 启动 WebSocket 监听
+    // 建立连接并启动监听；连接断开后按带抖动的指数退避自动重连
     pub fn start_listening(&mut self) {
-        let ws = match &self.ws {
-            Some(ws) => ws,
-            None => {
-                console::error_1(&"Cannot start listening: WebSocket not initialized.".into());
+        self.closed.set(false);
+        self.attempt.set(0);
+        self.connect();
+        self.start_ping();
+    }
+
+    // 主动关闭连接，不再重连
+    pub fn close(&self) {
+        self.closed.set(true);
+        if let Some(conn) = self.connection.borrow_mut().take() {
+            detach(&conn.ws);
+            let _ = conn.ws.close();
+        }
+        if let (Some(w), Some(timer)) = (window(), self.ping_timer.borrow_mut().take()) {
+            w.clear_interval_with_handle(timer.id);
+        }
+        self.emit_state(ConnectionState::Closed);
+    }
+
+    fn connect(&self) {
+        if self.attempt.get() == 0 {
+            self.emit_state(ConnectionState::Connecting);
+        }
+
+        // 按偏好顺序提供子协议，由服务端选择编码
+        let protocols = js_sys::Array::new();
+        for protocol in codec::PROTOCOLS {
+            protocols.push(&protocol.into());
+        }
+        let ws = match WebSocket::new_with_str_sequence(&self.url, &protocols) {
+            Ok(ws) => ws,
+            Err(_) => {
+                console::error_1(&format!("Failed to create WebSocket connection to {}.", self.url).into());
+                self.schedule_reconnect();
                 return;
             }
         };
+        ws.set_binary_type(web_sys::BinaryType::Arraybuffer);
 
-        // 克隆回调
-        let on_game_state = self.on_game_state.clone();
-        let on_matching_status = self.on_matching_status.clone();
-        let on_game_over = self.on_game_over.clone();
+        // 监听连接成功事件
+        let on_open = {
+            let client = self.clone();
+            Closure::wrap(Box::new(move || {
+                console::log_1(&"WebSocket connection established successfully!".into());
+                client.attempt.set(0);
+                // 服务端未选择子协议（旧版服务端）时 protocol() 为空，退回 JSON
+                let codec = Codec::from_protocol(&client.ws_protocol());
+                console::log_1(&format!("WebSocket codec: {:?}", codec).into());
+                client.codec.set(codec);
+                // 之前拿到过令牌说明是重连，先请求恢复会话
+                let token = client.session_token.borrow().clone();
+                if let Some(token) = token {
+                    client.send(GameMessage::Resume { token });
+                }
+                // 每次连接都上报持久玩家标识，用于积分与按积分匹配
+                if let Some(key) = config::player_key() {
+                    client.send(GameMessage::Identify { key });
+                }
+                if let Some(profile) = config::saved_profile() {
+                    client.send(GameMessage::SetProfile { profile });
+                }
+                // 之前登录过则凭登录令牌自动登录
+                if let Some(token) = config::auth_token() {
+                    client.send(GameMessage::Authenticate { token });
+                }
+                client.emit_state(ConnectionState::Open);
+            }) as Box<dyn FnMut()>)
+        };
 
         // 监听后端消息
-        let msg_closure = Closure::wrap(Box::new(move |e: MessageEvent| {
-            let text = match e.data().as_string() {
-                Some(t) => t,
-                None => {
-                    console::warn_1(&"Received non-string WebSocket message.".into());
-                    return;
+        let on_message = {
+            let client = self.clone();
+            Closure::wrap(Box::new(move |e: MessageEvent| {
+                client.dispatch(e);
+            }) as Box<dyn FnMut(MessageEvent)>)
+        };
+
+        // 监听连接关闭，非主动关闭时安排重连
+        let on_close = {
+            let client = self.clone();
+            Closure::wrap(Box::new(move |e: CloseEvent| {
+                console::warn_1(&format!(
+                    "WebSocket connection closed. Code: {}, Reason: {}",
+                    e.code(),
+                    e.reason()
+                ).into());
+                if !client.closed.get() {
+                    client.schedule_reconnect();
                 }
-            };
+            }) as Box<dyn FnMut(CloseEvent)>)
+        };
 
-            console::log_1(&format!("WS recv: {}", text).into());
+        ws.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        ws.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        ws.set_onclose(Some(on_close.as_ref().unchecked_ref()));
 
-            match serde_json::from_str::<GameMessage>(&text) {
-                Ok(GameMessage::GameState(state)) => {
-                    if let Some(cb) = on_game_state.clone() {
-                        cb.emit(state);
-                    }
+        let old = self.connection.borrow_mut().replace(Connection {
+            ws,
+            _on_open: on_open,
+            _on_message: on_message,
+            _on_close: on_close,
+        });
+        if let Some(old) = old {
+            detach(&old.ws);
+        }
+    }
+
+    fn schedule_reconnect(&self) {
+        let attempt = self.attempt.get() + 1;
+        if attempt > MAX_RECONNECT_ATTEMPTS {
+            console::error_1(&"WebSocket reconnect attempts exhausted.".into());
+            self.closed.set(true);
+            self.emit_state(ConnectionState::Closed);
+            return;
+        }
+        self.attempt.set(attempt);
+
+        let delay_ms = backoff_delay(attempt);
+        self.emit_state(ConnectionState::Reconnecting { attempt, delay_ms });
+
+        let client = self.clone();
+        let reconnect = Closure::once_into_js(move || {
+            if !client.closed.get() {
+                client.connect();
+            }
+        });
+        let scheduled = window().map(|w| {
+            w.set_timeout_with_callback_and_timeout_and_arguments_0(
+                reconnect.unchecked_ref(),
+                delay_ms as i32,
+            )
+        });
+        if !matches!(scheduled, Some(Ok(_))) {
+            console::error_1(&"Failed to schedule WebSocket reconnect.".into());
+        }
+    }
+
+    // 定时发送 Ping 测量往返延迟（仅在连接打开时发送）
+    fn start_ping(&self) {
+        if self.ping_timer.borrow().is_some() {
+            return;
+        }
+        let client = self.clone();
+        let ping = Closure::wrap(Box::new(move || {
+            let open = client
+                .connection
+                .borrow()
+                .as_ref()
+                .is_some_and(|conn| conn.ws.ready_state() == WebSocket::OPEN);
+            if open {
+                client.send(GameMessage::Ping { client_time: js_sys::Date::now() });
+            }
+        }) as Box<dyn FnMut()>);
+        let id = window().and_then(|w| {
+            w.set_interval_with_callback_and_timeout_and_arguments_0(
+                ping.as_ref().unchecked_ref(),
+                PING_INTERVAL_MS,
+            )
+            .ok()
+        });
+        match id {
+            Some(id) => *self.ping_timer.borrow_mut() = Some(PingTimer { id, _on_tick: ping }),
+            None => console::error_1(&"Failed to start WebSocket ping timer.".into()),
+        }
+    }
+
+    fn ws_protocol(&self) -> String {
+        self.connection
+            .borrow()
+            .as_ref()
+            .map(|conn| conn.ws.protocol())
+            .unwrap_or_default()
+    }
+
+    // 解析后端消息并分发给对应回调
+    fn dispatch(&self, e: MessageEvent) {
+        // 文本帧为 JSON，二进制帧（ArrayBuffer）为 MessagePack
+        let data = e.data();
+        let parsed = if let Some(text) = data.as_string() {
+            codec::decode_text(&text)
+        } else if let Ok(buffer) = data.dyn_into::<js_sys::ArrayBuffer>() {
+            let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+            codec::decode_binary(&bytes)
+        } else {
+            console::warn_1(&"Received unsupported WebSocket frame.".into());
+            return;
+        };
+
+        match parsed {
+            Ok(GameMessage::GameState(state)) => {
+                if self.is_stale(&state) {
+                    self.net.borrow_mut().stats.stale_frames += 1;
+                    console::warn_1(&format!("Discarding stale state at tick {}.", state.tick).into());
+                    return;
                 }
-                Ok(GameMessage::MatchingStatus { current, required }) => {
-                    if let Some(cb) = on_matching_status.clone() {
-                        cb.emit((current, required));
-                    }
+                self.net
+                    .borrow_mut()
+                    .record_state(state.tick, state.server_time_ms, js_sys::Date::now());
+                self.awaiting_snapshot.set(false);
+                *self.last_state.borrow_mut() = Some(state.clone());
+                if let Some(cb) = self.handlers.on_game_state.clone() {
+                    cb.emit(state);
                 }
-                Ok(GameMessage::GameOver { rankings }) => {
-                    if let Some(cb) = on_game_over.clone() {
-                        cb.emit(rankings);
-                    }
+            }
+            Ok(GameMessage::StateDelta(delta)) => {
+                self.apply_delta(delta);
+            }
+            Ok(GameMessage::MatchingStatus { current, required }) => {
+                if let Some(cb) = self.handlers.on_matching_status.clone() {
+                    cb.emit((current, required));
                 }
-                Ok(other) => {
-                    console::log_1(&format!("WS received other message: {:?}", other).into());
+            }
+            Ok(GameMessage::GameOver { rankings }) => {
+                if let Some(cb) = self.handlers.on_game_over.clone() {
+                    cb.emit(rankings);
                 }
-                Err(e) => {
-                    console::error_1(&format!("Failed to parse WebSocket message: {}", e).into());
+            }
+            Ok(GameMessage::Welcome { player_id, room_id }) => {
+                if let Some(cb) = self.handlers.on_welcome.clone() {
+                    cb.emit((player_id, room_id));
+                }
+            }
+            Ok(GameMessage::Pong { client_time, .. }) => {
+                let stats = {
+                    let mut net = self.net.borrow_mut();
+                    net.record_pong(client_time, js_sys::Date::now());
+                    net.stats
+                };
+                if let Some(cb) = self.handlers.on_net_stats.clone() {
+                    cb.emit(stats);
+                }
+            }
+            Ok(GameMessage::Session { token }) => {
+                *self.session_token.borrow_mut() = Some(token);
+            }
+            Ok(GameMessage::RoomList { rooms }) => {
+                if let Some(cb) = self.handlers.on_room_list.clone() {
+                    cb.emit(rooms);
+                }
+            }
+            Ok(GameMessage::RoomUpdate(details)) => {
+                if let Some(cb) = self.handlers.on_room_update.clone() {
+                    cb.emit(Some(details));
                 }
             }
-        }) as Box<dyn FnMut(MessageEvent)>);
+            Ok(GameMessage::RoomLeft) => {
+                if let Some(cb) = self.handlers.on_room_update.clone() {
+                    cb.emit(None);
+                }
+            }
+            Ok(GameMessage::RoomError { message }) => {
+                if let Some(cb) = self.handlers.on_room_error.clone() {
+                    cb.emit(message);
+                }
+            }
+            Ok(GameMessage::Rating { rating, games }) => {
+                if let Some(cb) = self.handlers.on_rating.clone() {
+                    cb.emit((rating, games));
+                }
+            }
+            Ok(GameMessage::Profile { profile }) => {
+                if let Some(cb) = self.handlers.on_profile.clone() {
+                    cb.emit(profile);
+                }
+            }
+            Ok(GameMessage::ProfileError { message }) => {
+                if let Some(cb) = self.handlers.on_profile_error.clone() {
+                    cb.emit(message);
+                }
+            }
+            Ok(GameMessage::LoggedIn { username, token }) => {
+                config::save_auth_token(Some(&token));
+                if let Some(cb) = self.handlers.on_account.clone() {
+                    cb.emit(Some(username));
+                }
+            }
+            Ok(GameMessage::LoggedOut) => {
+                config::save_auth_token(None);
+                if let Some(cb) = self.handlers.on_account.clone() {
+                    cb.emit(None);
+                }
+            }
+            Ok(GameMessage::AuthError { message }) => {
+                if let Some(cb) = self.handlers.on_auth_error.clone() {
+                    cb.emit(message);
+                }
+            }
+            Ok(GameMessage::Leaderboard(board)) => {
+                if let Some(cb) = self.handlers.on_leaderboard.clone() {
+                    cb.emit(board);
+                }
+            }
+            Ok(GameMessage::PlayerStats(stats)) => {
+                if let Some(cb) = self.handlers.on_stats.clone() {
+                    cb.emit(stats);
+                }
+            }
+            Ok(other) => {
+                console::log_1(&format!("WS received other message: {:?}", other).into());
+            }
+            Err(e) => {
+                console::error_1(&format!("Failed to parse WebSocket message: {}", e).into());
+            }
+        }
+    }
+
+    // 同一局中 tick 倒退的状态视为过期（乱序到达）
+    fn is_stale(&self, state: &GameState) -> bool {
+        self.last_state.borrow().as_ref().is_some_and(|last| {
+            last.room_id == state.room_id
+                && last.game_started
+                && state.game_started
+                && state.tick < last.tick
+        })
+    }
 
-        // 监听连接关闭
-        let close_closure = Closure::wrap(Box::new(move |e: CloseEvent| {
-            console::warn_1(&format!(
-                "WebSocket connection closed. Code: {}, Reason: {}",
-                e.code(),
-                e.reason()
-            ).into());
-        }) as Box<dyn FnMut(CloseEvent)>);
+    // 在最近的状态上应用增量：过期的增量直接丢弃；基准 tick 不匹配说明丢了帧，请求完整快照
+    fn apply_delta(&self, delta: StateDelta) {
+        let last_tick = self.last_state.borrow().as_ref().map(|s| s.tick);
+        if last_tick.is_some_and(|tick| delta.tick <= tick) {
+            self.net.borrow_mut().stats.stale_frames += 1;
+            return;
+        }
+
+        let next = self
+            .last_state
+            .borrow()
+            .as_ref()
+            .and_then(|state| delta::apply(state, &delta));
+        match next {
+            Some(state) => {
+                self.net
+                    .borrow_mut()
+                    .record_state(state.tick, state.server_time_ms, js_sys::Date::now());
+                *self.last_state.borrow_mut() = Some(state.clone());
+                if let Some(cb) = self.handlers.on_game_state.clone() {
+                    cb.emit(state);
+                }
+            }
+            None => {
+                if !self.awaiting_snapshot.replace(true) {
+                    console::warn_1(&format!(
+                        "State delta gap at base tick {}, requesting snapshot.",
+                        delta.base_tick
+                    ).into());
+                    self.send(GameMessage::RequestSnapshot);
+                }
+            }
+        }
+    }
 
-        ws.set_onmessage(Some(msg_closure.as_ref().unchecked_ref()));
-        ws.set_onclose(Some(close_closure.as_ref().unchecked_ref()));
-        
-        msg_closure.forget();
-        close_closure.forget();
+    fn emit_state(&self, state: ConnectionState) {
+        if let Some(cb) = &self.handlers.on_connection_state {
+            cb.emit(state);
+        }
     }
-}
\ No newline at end of file
+}
+
+// 带抖动的指数退避：在 [d/2, d] 中随机取值，d = min(BASE * 2^(attempt-1), MAX)
+fn backoff_delay(attempt: u32) -> u32 {
+    let exp = RECONNECT_BASE_DELAY_MS.saturating_mul(1 << (attempt - 1).min(16));
+    let capped = exp.min(RECONNECT_MAX_DELAY_MS);
+    let half = capped / 2;
+    half + (js_sys::Math::random() * half as f64) as u32
+}
+
+// 解除旧连接上的事件处理，避免已释放的闭包被调用
+fn detach(ws: &WebSocket) {
+    ws.set_onopen(None);
+    ws.set_onmessage(None);
+    ws.set_onclose(None);
+}